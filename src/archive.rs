@@ -0,0 +1,266 @@
+use core::fmt;
+use std::{
+    fs::{self, File},
+    io::{self, BufReader, BufWriter, Read, Write},
+    path::Path,
+};
+
+use bincode::{deserialize_from, Options};
+
+use crate::{
+    compression,
+    locale::{fill, messages},
+    store::{self, MergeReport, Snapshot, StoreError},
+};
+
+/// First bytes of an archive, different from the ones of the caches
+const MAGIC: &[u8; 4] = b"SCPA";
+/// Bumped on every change of [`Snapshot`], older archives go through [`migrate`]
+const ARCHIVE_VERSION: u16 = 1;
+/// Upper bound of the decompressed snapshot, a larger size in the header is a corrupted file
+const MAX_SIZE: u64 = 512 * 1024 * 1024;
+
+#[derive(Debug)]
+pub enum ArchiveError {
+    Io(io::Error),
+    /// The file doesn't start with the magic
+    NotAnArchive,
+    /// Written by a newer version of the app
+    UnsupportedVersion(u16),
+    /// The file is truncated or its checksum doesn't match
+    Corrupted,
+    /// The store doesn't fit into an archive, its size in bytes
+    TooLarge(u64),
+    Store(StoreError),
+}
+
+impl std::error::Error for ArchiveError {}
+
+impl fmt::Display for ArchiveError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ArchiveError::Io(e) => write!(f, "{}", fill(messages().archive_failed, &[e])),
+            ArchiveError::NotAnArchive => write!(f, "{}", messages().not_an_archive),
+            ArchiveError::UnsupportedVersion(v) => {
+                write!(
+                    f,
+                    "{}",
+                    fill(messages().archive_newer, &[v, &ARCHIVE_VERSION])
+                )
+            }
+            ArchiveError::Corrupted => write!(f, "{}", messages().archive_corrupted),
+            ArchiveError::TooLarge(size) => write!(
+                f,
+                "{}",
+                fill(
+                    messages().archive_too_large,
+                    &[&size.div_ceil(1024 * 1024), &(MAX_SIZE / 1024 / 1024)]
+                )
+            ),
+            ArchiveError::Store(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl From<io::Error> for ArchiveError {
+    fn from(e: io::Error) -> Self {
+        ArchiveError::Io(e)
+    }
+}
+
+impl From<StoreError> for ArchiveError {
+    fn from(e: StoreError) -> Self {
+        ArchiveError::Store(e)
+    }
+}
+
+/**
+Writes the store into a single file to share it: the magic, the version, the
+size and CRC32 of the data, then the [`Snapshot`] in bincode compressed with
+zstd. The file is renamed into place only once it is complete.
+*/
+pub fn export(path: &Path, favorites: bool) -> Result<Snapshot, ArchiveError> {
+    let snapshot = store::snapshot(favorites)?;
+    let data = bincode::serialize(&snapshot).map_err(|_| ArchiveError::Corrupted)?;
+
+    // A larger archive would be refused by `import` anyway
+    let size = data.len() as u64;
+    if size > MAX_SIZE {
+        return Err(ArchiveError::TooLarge(size));
+    }
+    let compressed = compression::compress(&data, None)?;
+
+    let tmp = path.with_extension("tmp");
+    let written = write(&tmp, &data, &compressed).and_then(|_| Ok(fs::rename(&tmp, path)?));
+    if written.is_err() {
+        let _ = fs::remove_file(&tmp);
+    }
+    written?;
+
+    Ok(snapshot)
+}
+
+fn write(path: &Path, data: &[u8], compressed: &[u8]) -> Result<(), ArchiveError> {
+    let mut f = BufWriter::new(File::create(path)?);
+    f.write_all(MAGIC)?;
+    for field in [
+        bincode::serialize(&ARCHIVE_VERSION),
+        bincode::serialize(&(data.len() as u64)),
+        bincode::serialize(&crc32fast::hash(data)),
+    ] {
+        f.write_all(&field.map_err(|_| ArchiveError::Corrupted)?)?;
+    }
+    f.write_all(compressed)?;
+
+    f.into_inner().map_err(|e| e.into_error())?.sync_all()?;
+
+    Ok(())
+}
+
+/// Reads an archive and merges it into the store, see [`store::merge`]
+pub fn import(path: &Path) -> Result<MergeReport, ArchiveError> {
+    let snapshot = read(path)?;
+
+    Ok(store::merge(&snapshot)?)
+}
+
+fn read(path: &Path) -> Result<Snapshot, ArchiveError> {
+    let mut f = BufReader::new(File::open(path)?);
+
+    let mut magic = [0; 4];
+    if f.read_exact(&mut magic).is_err() || &magic != MAGIC {
+        return Err(ArchiveError::NotAnArchive);
+    }
+
+    let version: u16 = deserialize_from(&mut f).map_err(|_| ArchiveError::Corrupted)?;
+    if version > ARCHIVE_VERSION {
+        return Err(ArchiveError::UnsupportedVersion(version));
+    }
+    let size: u64 = deserialize_from(&mut f).map_err(|_| ArchiveError::Corrupted)?;
+    let checksum: u32 = deserialize_from(&mut f).map_err(|_| ArchiveError::Corrupted)?;
+    if size > MAX_SIZE {
+        return Err(ArchiveError::Corrupted);
+    }
+
+    let mut compressed = Vec::new();
+    f.read_to_end(&mut compressed)?;
+    let data = compression::decompress(&compressed, size as usize, None)
+        .map_err(|_| ArchiveError::Corrupted)?;
+    if data.len() as u64 != size || crc32fast::hash(&data) != checksum {
+        return Err(ArchiveError::Corrupted);
+    }
+
+    migrate(version, &data)
+}
+
+/// Decodes the snapshot of any known version of the archive into the current one
+fn migrate(version: u16, data: &[u8]) -> Result<Snapshot, ArchiveError> {
+    match version {
+        ARCHIVE_VERSION => bincode::DefaultOptions::new()
+            .with_fixint_encoding()
+            .allow_trailing_bytes()
+            .with_limit(MAX_SIZE)
+            .deserialize(data)
+            .map_err(|_| ArchiveError::Corrupted),
+        v => Err(ArchiveError::UnsupportedVersion(v)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        paths,
+        store::tests::{article, object, temp_store},
+    };
+
+    /// Header with the checksum of `data` followed by `data` as it is
+    fn header(version: u16, size: u64, data: &[u8]) -> Vec<u8> {
+        let mut file = MAGIC.to_vec();
+        file.extend(bincode::serialize(&version).unwrap());
+        file.extend(bincode::serialize(&size).unwrap());
+        file.extend(bincode::serialize(&crc32fast::hash(data)).unwrap());
+        file.extend(data);
+        file
+    }
+
+    #[test]
+    fn export_and_import_round_trip() {
+        let _store = temp_store();
+        store::cache_objects(&[object("173")]).unwrap();
+        store::cache_article("scp-173", &article("scp-173", "Statue")).unwrap();
+        store::toggle_favorite("scp-173").unwrap();
+        let path = paths::data_file("bundle.scpa");
+
+        let snapshot = export(&path, true).unwrap();
+        assert_eq!(snapshot.favorites, ["scp-173"]);
+        assert!(!path.with_extension("tmp").exists());
+
+        store::clear().unwrap();
+        store::toggle_favorite("scp-173").unwrap();
+        let report = import(&path).unwrap();
+
+        assert_eq!((report.objects, report.articles), (1, 1));
+        assert_eq!(report.favorites, 1);
+        assert_eq!(
+            store::decache_article("scp-173").unwrap().0.source,
+            "Statue"
+        );
+    }
+
+    #[test]
+    fn rejects_other_files() {
+        let _store = temp_store();
+        let path = paths::data_file("other.scpa");
+        fs::write(&path, b"SCPE and something else").unwrap();
+
+        assert!(matches!(import(&path), Err(ArchiveError::NotAnArchive)));
+    }
+
+    #[test]
+    fn rejects_newer_versions() {
+        let _store = temp_store();
+        let path = paths::data_file("newer.scpa");
+        fs::write(&path, header(ARCHIVE_VERSION + 1, 0, &[])).unwrap();
+
+        assert!(matches!(
+            import(&path),
+            Err(ArchiveError::UnsupportedVersion(v)) if v == ARCHIVE_VERSION + 1
+        ));
+    }
+
+    #[test]
+    fn rejects_a_huge_size_before_decompressing() {
+        let _store = temp_store();
+        let path = paths::data_file("huge.scpa");
+        let data = compression::compress(b"data", None).unwrap();
+        fs::write(&path, header(ARCHIVE_VERSION, 1 << 44, &data)).unwrap();
+
+        assert!(matches!(import(&path), Err(ArchiveError::Corrupted)));
+    }
+
+    #[test]
+    fn rejects_truncated_archives() {
+        let _store = temp_store();
+        store::cache_objects(&[object("173")]).unwrap();
+        let path = paths::data_file("truncated.scpa");
+        export(&path, false).unwrap();
+
+        let file = fs::read(&path).unwrap();
+        fs::write(&path, &file[..file.len() - 4]).unwrap();
+
+        assert!(matches!(import(&path), Err(ArchiveError::Corrupted)));
+    }
+
+    #[test]
+    fn failed_exports_leave_no_temporary_file() {
+        let _store = temp_store();
+        store::cache_objects(&[object("173")]).unwrap();
+        // A file can't be renamed over a directory with something in it
+        let path = paths::data_file("taken.scpa");
+        fs::create_dir_all(path.join("inside")).unwrap();
+
+        assert!(matches!(export(&path, false), Err(ArchiveError::Io(_))));
+        assert!(!path.with_extension("tmp").exists());
+    }
+}
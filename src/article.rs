@@ -0,0 +1,703 @@
+use unicode_width::UnicodeWidthChar;
+
+use crate::parsing::ApiObjectResult;
+
+/// Link of the article, `[[[target|label]]]` or `[[[target]]]` in the FTML source
+#[derive(Debug, Clone, PartialEq)]
+pub struct Link {
+    /// Page id on the site, e.g. `scp-003`
+    pub page: String,
+    pub label: String,
+}
+
+/// Piece of a displayed line, `link` is an index into [`Article::links`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct Segment {
+    pub text: String,
+    pub link: Option<usize>,
+}
+
+pub type Line = Vec<Segment>;
+
+/// Highlight of a piece of a line found by [`Article::find`]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Mark {
+    None,
+    Match,
+    CurrentMatch,
+}
+
+/// Occurrence of the find query, split into pieces when a line is wrapped inside it
+#[derive(Debug, Clone, PartialEq)]
+struct Match {
+    /// Wrapped line, first char and length in chars of every piece
+    pieces: Vec<(usize, usize, usize)>,
+}
+
+impl Match {
+    /// First wrapped line of the occurrence
+    fn line(&self) -> usize {
+        self.pieces.first().map_or(0, |p| p.0)
+    }
+}
+
+/**
+Article opened in the explorer. The source is kept as it comes from the
+API except links, which are replaced by their labels and remembered, so
+they can be followed from the keyboard or by a click.
+*/
+#[derive(Debug, Clone)]
+pub struct Article {
+    pub page_id: String,
+    pub title: String,
+    pub links: Vec<Link>,
+    /// Source as it came from the API, kept to save the article with the tabs
+    raw: String,
+    /// Lines of the source with links resolved, before wrapping
+    source: Vec<Line>,
+    /// Lines wrapped to `wrapped_width`, rebuilt when the width changes
+    wrapped: Vec<Line>,
+    /// First wrapped line of every source line
+    wrapped_starts: Vec<usize>,
+    wrapped_width: usize,
+    /// Whether long lines are wrapped or scrolled horizontally
+    wrap: bool,
+    pub scroll: (u16, u16),
+    /// Rows of the explorer, updated on every render
+    pub viewport: usize,
+    /// Columns of the explorer, updated on every render
+    columns: usize,
+    pub selected_link: Option<usize>,
+    /// Query of the in-article find, matches are searched in source lines
+    query: String,
+    matches: Vec<Match>,
+    current_match: Option<usize>,
+}
+
+/// Turns the target of a wiki link into the id of the page on the site
+fn page_id(target: &str) -> Option<String> {
+    let target = target.trim().trim_start_matches('*');
+    if target.is_empty()
+        || target.starts_with('#')
+        || target.starts_with("http://")
+        || target.starts_with("https://")
+    {
+        return None;
+    }
+
+    let target = target.split('#').next().unwrap_or(target);
+    let target = target.trim_start_matches('/').trim();
+
+    Some(
+        target
+            .to_lowercase()
+            .split_whitespace()
+            .collect::<Vec<&str>>()
+            .join("-"),
+    )
+}
+
+fn push_text(line: &mut Line, text: &str, link: Option<usize>) {
+    if text.is_empty() {
+        return;
+    }
+
+    match line.last_mut() {
+        Some(last) if last.link == link => last.text.push_str(text),
+        _ => line.push(Segment {
+            text: text.to_string(),
+            link,
+        }),
+    }
+}
+
+/// Splits the FTML source into lines, replacing links by their labels
+fn parse_source(source: &str, links: &mut Vec<Link>) -> Vec<Line> {
+    source
+        .lines()
+        .map(|raw| {
+            let mut line: Line = Vec::new();
+            let mut rest = raw;
+
+            while let Some(start) = rest.find("[[[") {
+                let Some(len) = rest[start + 3..].find("]]]") else {
+                    break;
+                };
+
+                push_text(&mut line, &rest[..start], None);
+
+                let inner = &rest[start + 3..start + 3 + len];
+                let (target, label) = match inner.split_once('|') {
+                    Some((target, label)) => (target, label.trim()),
+                    None => (inner, inner.trim()),
+                };
+
+                match page_id(target) {
+                    Some(page) => {
+                        links.push(Link {
+                            page,
+                            label: label.to_string(),
+                        });
+                        push_text(&mut line, label, Some(links.len() - 1));
+                    }
+                    None => push_text(&mut line, label, None),
+                }
+
+                rest = &rest[start + 3 + len + 3..];
+            }
+
+            push_text(&mut line, rest, None);
+            line
+        })
+        .collect()
+}
+
+/// Greedy word wrap of a single line, words longer than `width` are broken
+fn wrap_line(line: &Line, width: usize) -> Vec<Line> {
+    let mut lines: Vec<Line> = Vec::new();
+    let mut current: Line = Vec::new();
+    let mut current_width = 0;
+
+    // Words together with the spaces in front of them
+    let mut words: Vec<Vec<(char, Option<usize>)>> = Vec::new();
+    let mut word: Vec<(char, Option<usize>)> = Vec::new();
+    let mut in_spaces = true;
+    for segment in line {
+        for c in segment.text.chars() {
+            if !c.is_whitespace() {
+                in_spaces = false;
+            } else if !in_spaces {
+                words.push(std::mem::take(&mut word));
+                in_spaces = true;
+            }
+            word.push((c, segment.link));
+        }
+    }
+    words.push(word);
+
+    let char_width = |c: char| UnicodeWidthChar::width(c).unwrap_or(0);
+
+    for word in words {
+        let word_width: usize = word.iter().map(|(c, _)| char_width(*c)).sum();
+
+        let word: &[(char, Option<usize>)] = if current_width + word_width > width {
+            if !current.is_empty() {
+                lines.push(std::mem::take(&mut current));
+                current_width = 0;
+            }
+
+            // A new line doesn't start with spaces
+            let start = word
+                .iter()
+                .position(|(c, _)| !c.is_whitespace())
+                .unwrap_or(word.len());
+            &word[start..]
+        } else {
+            &word
+        };
+
+        for (c, link) in word {
+            let w = char_width(*c);
+            if current_width + w > width && current_width > 0 {
+                lines.push(std::mem::take(&mut current));
+                current_width = 0;
+            }
+
+            let mut buf = [0; 4];
+            push_text(&mut current, c.encode_utf8(&mut buf), *link);
+            current_width += w;
+        }
+    }
+
+    lines.push(current);
+    lines
+}
+
+impl Article {
+    pub fn new(page_id: String, title: String, source: &str) -> Self {
+        let mut links = Vec::new();
+        let lines = parse_source(source, &mut links);
+
+        Article {
+            page_id,
+            title,
+            links,
+            raw: source.to_string(),
+            source: lines,
+            wrapped: Vec::new(),
+            wrapped_starts: Vec::new(),
+            wrapped_width: 0,
+            wrap: true,
+            scroll: (0, 0),
+            viewport: 1,
+            columns: 1,
+            selected_link: None,
+            query: String::new(),
+            matches: Vec::new(),
+            current_match: None,
+        }
+    }
+
+    /// Lines wrapped to fit `width` columns, or as they are if wrapping is off
+    pub fn lines(&mut self, width: usize) -> &[Line] {
+        self.columns = width.max(1);
+        let width = if self.wrap { self.columns } else { usize::MAX };
+
+        if self.wrapped_width != width {
+            self.wrapped.clear();
+            self.wrapped_starts.clear();
+            for line in &self.source {
+                self.wrapped_starts.push(self.wrapped.len());
+                self.wrapped.extend(wrap_line(line, width));
+            }
+            self.wrapped_width = width;
+            self.update_matches();
+        }
+
+        &self.wrapped
+    }
+
+    pub fn raw(&self) -> &str {
+        &self.raw
+    }
+
+    pub fn is_wrapped(&self) -> bool {
+        self.wrap
+    }
+
+    pub fn toggle_wrap(&mut self) {
+        self.wrap = !self.wrap;
+        self.scroll.1 = 0;
+    }
+
+    /// Scrolls by `delta` lines, never past the last screen of text
+    pub fn scroll_by(&mut self, delta: isize) {
+        let max = self.wrapped.len().saturating_sub(self.viewport);
+        self.scroll.0 = (self.scroll.0 as usize)
+            .saturating_add_signed(delta)
+            .min(max) as u16;
+    }
+
+    /// Scrolls by `delta` columns when wrapping is off
+    pub fn scroll_columns(&mut self, delta: isize) {
+        if self.wrap {
+            return;
+        }
+
+        let longest = self
+            .wrapped
+            .iter()
+            .map(|l| {
+                l.iter()
+                    .flat_map(|s| s.text.chars())
+                    .map(|c| UnicodeWidthChar::width(c).unwrap_or(0))
+                    .sum::<usize>()
+            })
+            .max()
+            .unwrap_or(0);
+        let max = longest.saturating_sub(self.columns);
+
+        self.scroll.1 = (self.scroll.1 as usize)
+            .saturating_add_signed(delta)
+            .min(max) as u16;
+    }
+
+    /// Scrolls by half the width of the explorer, to the right when `right`
+    pub fn scroll_half_width(&mut self, right: bool) {
+        let step = (self.columns / 2).max(1) as isize;
+        self.scroll_columns(if right { step } else { -step });
+    }
+
+    /// Line at the top of the explorer counting from 1, the number of lines
+    /// and how much of the article was read in percents
+    pub fn position(&self) -> (usize, usize, usize) {
+        let total = self.wrapped.len();
+        let top = self.scroll.0 as usize;
+        let read = ((top + self.viewport).min(total) * 100)
+            .checked_div(total)
+            .unwrap_or(100);
+
+        (top + 1, total, read)
+    }
+
+    /// Link under the column `x` of the wrapped line `y`
+    pub fn link_at(&self, y: usize, x: usize) -> Option<usize> {
+        let mut column = 0;
+
+        for segment in self.wrapped.get(y)? {
+            for c in segment.text.chars() {
+                column += UnicodeWidthChar::width(c).unwrap_or(0);
+                if column > x {
+                    return segment.link;
+                }
+            }
+        }
+
+        None
+    }
+
+    /// First wrapped line showing the link
+    pub fn link_line(&self, link: usize) -> Option<usize> {
+        self.wrapped
+            .iter()
+            .position(|l| l.iter().any(|s| s.link == Some(link)))
+    }
+
+    /// Selects the next link, or the previous one when `forward` is false
+    pub fn select_link(&mut self, forward: bool) {
+        if self.links.is_empty() {
+            return;
+        }
+
+        let last = self.links.len() - 1;
+        let top = self.scroll.0 as usize;
+        let below = |l: &usize| self.link_line(*l).is_some_and(|line| line >= top);
+        let above = |l: &usize| {
+            self.link_line(*l)
+                .is_some_and(|line| line < top + self.viewport)
+        };
+
+        // Without a selection start from the links on the screen
+        self.selected_link = Some(match (self.selected_link, forward) {
+            (None, true) => (0..=last).find(below).unwrap_or(0),
+            (None, false) => (0..=last).rev().find(above).unwrap_or(last),
+            (Some(i), true) if i >= last => 0,
+            (Some(i), true) => i + 1,
+            (Some(0), false) => last,
+            (Some(i), false) => i - 1,
+        });
+
+        if let Some(line) = self.selected_link.and_then(|l| self.link_line(l)) {
+            self.scroll_to(line);
+        }
+    }
+
+    /// Scrolls just enough for the wrapped line to become visible
+    fn scroll_to(&mut self, line: usize) {
+        let top = self.scroll.0 as usize;
+        if line < top {
+            self.scroll.0 = line as u16;
+        } else if line >= top + self.viewport {
+            self.scroll.0 = (line + 1 - self.viewport) as u16;
+        }
+    }
+
+    /// Finds every occurrence of `query` ignoring case, an empty query clears
+    /// the highlight. Returns the number of matches.
+    pub fn find(&mut self, query: &str) -> usize {
+        self.query = query.to_string();
+        self.update_matches();
+        self.matches.len()
+    }
+
+    /**
+    Searches the source lines, so an occurrence is found even when a line is
+    wrapped inside it, then maps the occurrences onto the wrapped lines.
+    */
+    fn update_matches(&mut self) {
+        self.matches.clear();
+        self.current_match = None;
+
+        let fold = |c: char| c.to_lowercase().next().unwrap_or(c);
+        let query: Vec<char> = self.query.chars().map(fold).collect();
+        // Not wrapped yet, `lines` searches again once it is
+        if query.is_empty() || self.wrapped_starts.len() != self.source.len() {
+            return;
+        }
+
+        for (i, line) in self.source.iter().enumerate() {
+            let text: Vec<char> = line.iter().flat_map(|s| s.text.chars()).map(fold).collect();
+
+            let mut starts = Vec::new();
+            let mut start = 0;
+            while start + query.len() <= text.len() {
+                if text[start..start + query.len()] == query[..] {
+                    starts.push(start);
+                    start += query.len();
+                } else {
+                    start += 1;
+                }
+            }
+            if starts.is_empty() {
+                continue;
+            }
+
+            let positions = self.wrapped_positions(i, &text);
+            for start in starts {
+                let mut pieces: Vec<(usize, usize, usize)> = Vec::new();
+                for &(y, x) in positions[start..start + query.len()].iter().flatten() {
+                    match pieces.last_mut() {
+                        Some((line, _, len)) if *line == y => *len += 1,
+                        _ => pieces.push((y, x, 1)),
+                    }
+                }
+                if !pieces.is_empty() {
+                    self.matches.push(Match { pieces });
+                }
+            }
+        }
+    }
+
+    /**
+    Wrapped line and column in chars of every char of the source line `i`,
+    `text` being its chars. Wrapping only drops the spaces at the start of
+    a wrapped line, those have no position.
+    */
+    fn wrapped_positions(&self, i: usize, text: &[char]) -> Vec<Option<(usize, usize)>> {
+        let first = self.wrapped_starts[i];
+        let last = self
+            .wrapped_starts
+            .get(i + 1)
+            .copied()
+            .unwrap_or(self.wrapped.len());
+
+        let mut wrapped = (first..last).flat_map(|y| {
+            self.wrapped[y]
+                .iter()
+                .flat_map(|s| s.text.chars())
+                .enumerate()
+                .map(move |(x, c)| (y, x, c.to_lowercase().next().unwrap_or(c)))
+        });
+
+        let mut next = wrapped.next();
+        text.iter()
+            .map(|c| match next {
+                Some((y, x, w)) if w == *c => {
+                    next = wrapped.next();
+                    Some((y, x))
+                }
+                _ => None,
+            })
+            .collect()
+    }
+
+    /**
+    Moves to the next match, or the previous one when `forward` is false,
+    wrapping around. Without a current match the search starts from the top
+    of the explorer. Returns the position of the match counting from 1.
+    */
+    pub fn next_match(&mut self, forward: bool) -> Option<usize> {
+        if self.matches.is_empty() {
+            return None;
+        }
+
+        let last = self.matches.len() - 1;
+        let top = self.scroll.0 as usize;
+        let i = match (self.current_match, forward) {
+            (Some(i), true) if i >= last => 0,
+            (Some(i), true) => i + 1,
+            (Some(0), false) => last,
+            (Some(i), false) => i - 1,
+            (None, true) => self
+                .matches
+                .iter()
+                .position(|m| m.line() >= top)
+                .unwrap_or(0),
+            (None, false) => self
+                .matches
+                .iter()
+                .rposition(|m| m.line() < top + self.viewport)
+                .unwrap_or(last),
+        };
+
+        self.current_match = Some(i);
+        self.scroll_to(self.matches[i].line());
+
+        Some(i + 1)
+    }
+
+    /// Position of the current match counting from 1 and the number of matches
+    pub fn match_position(&self) -> Option<(usize, usize)> {
+        if self.matches.is_empty() {
+            return None;
+        }
+
+        Some((self.current_match.map_or(0, |i| i + 1), self.matches.len()))
+    }
+
+    /// Pieces of the wrapped line `y` with their link and find highlight
+    pub fn marked(&self, y: usize) -> Vec<(String, Option<usize>, Mark)> {
+        let mut pieces: Vec<(String, Option<usize>, Mark)> = Vec::new();
+        let Some(line) = self.wrapped.get(y) else {
+            return pieces;
+        };
+
+        let matches: Vec<(usize, (usize, usize))> = self
+            .matches
+            .iter()
+            .enumerate()
+            .flat_map(|(i, m)| m.pieces.iter().map(move |p| (i, *p)))
+            .filter(|(_, (line, _, _))| *line == y)
+            .map(|(i, (_, start, len))| (i, (start, len)))
+            .collect();
+
+        let chars = line
+            .iter()
+            .flat_map(|s| s.text.chars().map(move |c| (c, s.link)));
+        for (x, (c, link)) in chars.enumerate() {
+            let mark = match matches
+                .iter()
+                .find(|(_, (start, len))| (*start..start + len).contains(&x))
+            {
+                Some((i, _)) if Some(*i) == self.current_match => Mark::CurrentMatch,
+                Some(_) => Mark::Match,
+                None => Mark::None,
+            };
+
+            match pieces.last_mut() {
+                Some((text, l, m)) if *l == link && *m == mark => text.push(c),
+                _ => pieces.push((c.to_string(), link, mark)),
+            }
+        }
+
+        pieces
+    }
+}
+
+impl From<ApiObjectResult> for Article {
+    fn from(r: ApiObjectResult) -> Self {
+        Article::new(r.page_id, r.title, &r.source)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn text(line: &Line) -> String {
+        line.iter().map(|s| s.text.as_str()).collect()
+    }
+
+    fn plain(s: &str) -> Line {
+        vec![Segment {
+            text: s.to_string(),
+            link: None,
+        }]
+    }
+
+    fn article(source: &str) -> Article {
+        Article::new("scp-173".to_string(), "SCP-173".to_string(), source)
+    }
+
+    #[test]
+    fn wrap_line_breaks_between_words() {
+        let lines: Vec<String> = wrap_line(&plain("one two three"), 8)
+            .iter()
+            .map(text)
+            .collect();
+
+        assert_eq!(lines, ["one two", "three"]);
+    }
+
+    #[test]
+    fn wrap_line_breaks_long_words() {
+        let lines: Vec<String> = wrap_line(&plain("abcdefghij"), 4)
+            .iter()
+            .map(text)
+            .collect();
+
+        assert_eq!(lines, ["abcd", "efgh", "ij"]);
+    }
+
+    #[test]
+    fn wrap_line_counts_wide_chars() {
+        let lines: Vec<String> = wrap_line(&plain("界界界"), 4).iter().map(text).collect();
+
+        assert_eq!(lines, ["界界", "界"]);
+    }
+
+    #[test]
+    fn wrap_line_keeps_links() {
+        let mut links = Vec::new();
+        let source = parse_source("see [[[scp-173|the statue]]] now", &mut links);
+        let lines = wrap_line(&source[0], 9);
+
+        assert_eq!(links[0].page, "scp-173");
+        assert_eq!(
+            lines.iter().map(text).collect::<Vec<_>>(),
+            ["see the", "statue", "now"]
+        );
+        assert_eq!(lines[0][1].link, Some(0));
+        assert_eq!(lines[1][0].link, Some(0));
+        assert_eq!(lines[2][0].link, None);
+    }
+
+    #[test]
+    fn wrap_line_keeps_an_empty_line() {
+        assert_eq!(wrap_line(&plain(""), 10), vec![Vec::new()]);
+    }
+
+    #[test]
+    fn find_ignores_case() {
+        let mut article = article("Keter and keter\nKETER");
+        article.lines(80);
+
+        assert_eq!(article.find("keter"), 3);
+        assert_eq!(article.next_match(true), Some(1));
+        assert_eq!(article.match_position(), Some((1, 3)));
+    }
+
+    #[test]
+    fn find_with_empty_query_clears_matches() {
+        let mut article = article("Keter");
+        article.lines(80);
+        article.find("keter");
+
+        assert_eq!(article.find(""), 0);
+        assert_eq!(article.match_position(), None);
+    }
+
+    #[test]
+    fn find_matches_across_wrapped_lines() {
+        let mut article = article("the old man walks");
+        article.lines(8);
+
+        assert_eq!(article.find("old man"), 1);
+        assert_eq!(
+            article.marked(0),
+            [
+                ("the ".to_string(), None, Mark::None),
+                ("old".to_string(), None, Mark::Match)
+            ]
+        );
+        assert_eq!(article.marked(1), [("man".to_string(), None, Mark::Match)]);
+        assert_eq!(article.marked(2), [("walks".to_string(), None, Mark::None)]);
+    }
+
+    #[test]
+    fn find_follows_the_width() {
+        let mut article = article("the old man walks");
+        article.lines(8);
+        article.find("man");
+        article.lines(80);
+
+        assert_eq!(article.match_position(), Some((0, 1)));
+        assert_eq!(article.marked(0)[1], ("man".to_string(), None, Mark::Match));
+    }
+
+    #[test]
+    fn next_match_wraps_around() {
+        let mut article = article("a\nb\na");
+        article.lines(80);
+        article.find("a");
+
+        assert_eq!(article.next_match(true), Some(1));
+        assert_eq!(article.next_match(true), Some(2));
+        assert_eq!(article.next_match(true), Some(1));
+        assert_eq!(article.next_match(false), Some(2));
+    }
+
+    #[test]
+    fn scrolls_by_half_the_width() {
+        let mut article = article(&"x".repeat(100));
+        article.toggle_wrap();
+        article.viewport = 50;
+        article.lines(20);
+
+        article.scroll_half_width(true);
+        assert_eq!(article.scroll.1, 10);
+        article.scroll_half_width(false);
+        article.scroll_half_width(false);
+        assert_eq!(article.scroll.1, 0);
+    }
+}
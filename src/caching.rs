@@ -1,26 +1,72 @@
 use core::fmt;
 use std::{
-    fs::File,
-    io::{BufReader, BufWriter},
+    fs::{self, File, OpenOptions},
+    io::{self, BufReader, BufWriter, Read, Seek, Write},
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime, UNIX_EPOCH},
 };
 
-use bincode::{deserialize_from, serialize_into};
+use bincode::deserialize_from;
+use fs2::FileExt;
+use serde::{de::DeserializeOwned, Serialize};
 
-use crate::parsing::ScpObject;
+use crate::{
+    locale::{fill, messages},
+    parsing::{ApiObjectResult, ScpObject},
+    paths,
+    searches::SavedSearch,
+    tabs::SavedTabs,
+};
 
+/// Objects scraped before the store, imported into it once
 const CACHE_O_PATH: &str = "cache_o.data";
+const SEARCHES_PATH: &str = "searches.data";
+const TABS_PATH: &str = "tabs.data";
+/// Directory in the cache with a file for every opened article, before the store
+const ARTICLES_DIR: &str = "articles";
+const EXTENSION: &str = "data";
+
+/// First bytes of every file, files without them were written before the header
+const MAGIC: &[u8; 4] = b"SCPE";
+/// Bumped on every change of the encoding, older files go through [`migrate`]
+const FORMAT_VERSION: u16 = 2;
+
+/// Goes after the magic and before the data
+#[derive(Debug)]
+struct Header {
+    version: u16,
+    /// Seconds since the unix epoch
+    created: u64,
+    /// CRC32 of the data, files before version 2 have none
+    checksum: Option<u32>,
+}
+
+impl Header {
+    fn new(data: &[u8]) -> Self {
+        Header {
+            version: FORMAT_VERSION,
+            created: seconds(SystemTime::now()),
+            checksum: Some(crc32fast::hash(data)),
+        }
+    }
 
-pub fn cache_objects(objects: Vec<ScpObject>) {
-    let path = std::env::current_dir()
-        .unwrap()
-        .as_path()
-        .join(CACHE_O_PATH);
-    let mut f = BufWriter::new(File::create(path).unwrap());
-    serialize_into(&mut f, &objects).unwrap();
+    fn created(&self) -> SystemTime {
+        UNIX_EPOCH + Duration::from_secs(self.created)
+    }
+}
+
+fn seconds(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs())
 }
-#[derive(Debug, PartialEq)]
+
+#[derive(Debug)]
 pub enum CacheError {
     FileCacheNotExists,
+    /// The file is truncated, its checksum doesn't match or it isn't a cache at all
+    Corrupted,
+    /// Written by a newer version of the app
+    UnsupportedVersion(u16),
+    Write(io::Error),
 }
 
 impl std::error::Error for CacheError {}
@@ -28,24 +74,365 @@ impl std::error::Error for CacheError {}
 impl fmt::Display for CacheError {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
-            CacheError::FileCacheNotExists => write!(f, "Cache not exists"),
+            CacheError::FileCacheNotExists => write!(f, "{}", messages().cache_missing),
+            CacheError::Corrupted => write!(f, "{}", messages().cache_corrupted),
+            CacheError::UnsupportedVersion(v) => {
+                write!(f, "{}", fill(messages().cache_newer, &[v, &FORMAT_VERSION]))
+            }
+            CacheError::Write(e) => write!(f, "{}", fill(messages().cache_write_failed, &[e])),
         }
     }
 }
 
-pub fn decache_objects() -> Result<Vec<ScpObject>, CacheError> {
-    let path = std::env::current_dir()
-        .unwrap()
-        .as_path()
-        .join(CACHE_O_PATH);
-    let o = File::open(path);
+/**
+Advisory lock on the `.lock` file next to a cache, released when dropped.
+Files are replaced on write, so the lock can't be held on them directly.
+*/
+struct Lock(File);
 
-    match o {
-        Ok(o) => {
-            let f = BufReader::new(o);
-            let objects: Vec<ScpObject> = deserialize_from(f).unwrap();
-            Ok(objects)
-        }
-        Err(_) => Err(CacheError::FileCacheNotExists),
+impl Lock {
+    fn open(path: &Path) -> io::Result<File> {
+        OpenOptions::new()
+            .create(true)
+            .truncate(false)
+            .write(true)
+            .open(path.with_extension("lock"))
+    }
+
+    /// Any number of instances may read at once
+    fn shared(path: &Path) -> io::Result<Lock> {
+        let file = Lock::open(path)?;
+        FileExt::lock_shared(&file)?;
+        Ok(Lock(file))
+    }
+
+    /// Only one instance writes, nobody reads meanwhile
+    fn exclusive(path: &Path) -> io::Result<Lock> {
+        let file = Lock::open(path)?;
+        FileExt::lock_exclusive(&file)?;
+        Ok(Lock(file))
+    }
+}
+
+impl Drop for Lock {
+    fn drop(&mut self) {
+        let _ = FileExt::unlock(&self.0);
+    }
+}
+
+/**
+Writes a temporary file and renames it over the old one, so a crash in the
+middle leaves either the old cache or the new one and never a part of it.
+*/
+fn write<T: Serialize + ?Sized>(path: &Path, value: &T) -> Result<(), CacheError> {
+    let data = bincode::serialize(value).map_err(|_| CacheError::Corrupted)?;
+    let header = Header::new(&data);
+
+    let _lock = Lock::exclusive(path).map_err(CacheError::Write)?;
+    let tmp = path.with_extension("tmp");
+    let mut f = BufWriter::new(File::create(&tmp).map_err(CacheError::Write)?);
+
+    f.write_all(MAGIC).map_err(CacheError::Write)?;
+    for field in [
+        bincode::serialize(&header.version),
+        bincode::serialize(&header.created),
+        bincode::serialize(&header.checksum.unwrap_or_default()),
+    ] {
+        f.write_all(&field.map_err(|_| CacheError::Corrupted)?)
+            .map_err(CacheError::Write)?;
+    }
+    f.write_all(&data).map_err(CacheError::Write)?;
+
+    let file = f
+        .into_inner()
+        .map_err(|e| CacheError::Write(e.into_error()))?;
+    file.sync_all().map_err(CacheError::Write)?;
+    fs::rename(&tmp, path).map_err(CacheError::Write)
+}
+
+/**
+Reads the header and leaves `f` at the start of the data. Files without the
+magic are version 0, created when they were last modified.
+*/
+fn read_header(f: &mut BufReader<File>) -> Result<Header, CacheError> {
+    let mut magic = [0; 4];
+    if f.read_exact(&mut magic).is_ok() && &magic == MAGIC {
+        let version: u16 = deserialize_from(&mut *f).map_err(|_| CacheError::Corrupted)?;
+        let created: u64 = deserialize_from(&mut *f).map_err(|_| CacheError::Corrupted)?;
+        let checksum = if version >= 2 {
+            Some(deserialize_from(&mut *f).map_err(|_| CacheError::Corrupted)?)
+        } else {
+            None
+        };
+
+        return Ok(Header {
+            version,
+            created,
+            checksum,
+        });
+    }
+
+    let modified = f
+        .get_ref()
+        .metadata()
+        .and_then(|m| m.modified())
+        .unwrap_or_else(|_| SystemTime::now());
+    f.rewind().map_err(|_| CacheError::Corrupted)?;
+
+    Ok(Header {
+        version: 0,
+        created: seconds(modified),
+        checksum: None,
+    })
+}
+
+/// Decodes the data of any known version of the format into the current one
+fn migrate<T: DeserializeOwned>(version: u16, data: &[u8]) -> Result<T, CacheError> {
+    match version {
+        // Version 0 is the same bincode without the header, 1 has no checksum
+        0..=FORMAT_VERSION => bincode::deserialize(data).map_err(|_| CacheError::Corrupted),
+        v => Err(CacheError::UnsupportedVersion(v)),
+    }
+}
+
+/**
+Reads a file of any version with the time it was created, older ones are
+written again in the current format
+*/
+fn read<T: Serialize + DeserializeOwned>(path: &Path) -> Result<(T, SystemTime), CacheError> {
+    let (header, data) = {
+        // A missing lock file only means nobody writes the cache right now
+        let _lock = Lock::shared(path).ok();
+
+        let file = File::open(path).map_err(|_| CacheError::FileCacheNotExists)?;
+        let mut f = BufReader::new(file);
+        let header = read_header(&mut f)?;
+
+        let mut data = Vec::new();
+        f.read_to_end(&mut data)
+            .map_err(|_| CacheError::Corrupted)?;
+        (header, data)
+    };
+
+    if header.checksum.is_some_and(|c| c != crc32fast::hash(&data)) {
+        return Err(CacheError::Corrupted);
+    }
+
+    let value = migrate(header.version, &data)?;
+
+    if header.version < FORMAT_VERSION {
+        // The data is still usable when the upgrade can't be written
+        let _ = write(path, &value);
+    }
+
+    Ok((value, header.created()))
+}
+
+/// Where the objects were cached before the store, versions before the XDG
+/// directories kept them in the current directory
+fn legacy_objects_paths() -> [PathBuf; 2] {
+    [paths::cache_file(CACHE_O_PATH), PathBuf::from(CACHE_O_PATH)]
+}
+
+/// Objects cached before the store with the time they were cached
+pub fn legacy_objects() -> Result<(Vec<ScpObject>, SystemTime), CacheError> {
+    legacy_objects_paths()
+        .iter()
+        .filter(|p| p.exists())
+        .map(|p| read(p))
+        .find(|r| r.is_ok())
+        .unwrap_or(Err(CacheError::FileCacheNotExists))
+}
+
+pub fn cache_searches(searches: &[SavedSearch]) -> Result<(), CacheError> {
+    write(&paths::data_file(SEARCHES_PATH), searches)
+}
+
+pub fn decache_searches() -> Result<Vec<SavedSearch>, CacheError> {
+    read(&paths::data_file(SEARCHES_PATH)).map(|(s, _)| s)
+}
+
+pub fn cache_tabs(tabs: &SavedTabs) -> Result<(), CacheError> {
+    write(&paths::data_file(TABS_PATH), tabs)
+}
+
+pub fn decache_tabs() -> Result<SavedTabs, CacheError> {
+    read(&paths::data_file(TABS_PATH)).map(|(t, _)| t)
+}
+
+/// Articles cached before the store, files that can't be read are skipped
+pub fn legacy_articles() -> Vec<(ApiObjectResult, SystemTime)> {
+    let Ok(dir) = fs::read_dir(paths::cache_dir(ARTICLES_DIR)) else {
+        return Vec::new();
+    };
+
+    dir.filter_map(|e| e.ok())
+        .map(|e| e.path())
+        .filter(|p| p.extension().is_some_and(|e| e == EXTENSION))
+        .filter_map(|p| read(&p).ok())
+        .collect()
+}
+
+/// Removes the caches imported into the store with their locks
+pub fn remove_legacy() {
+    for objects in legacy_objects_paths() {
+        let _ = fs::remove_file(objects.with_extension("lock"));
+        let _ = fs::remove_file(objects);
+    }
+    let _ = fs::remove_dir_all(paths::cache_dir(ARTICLES_DIR));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Empty directory of its own for every test
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir()
+            .join(format!("scpfoundation-explorer-{}", std::process::id()))
+            .join(name);
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn header_of(path: &Path) -> Header {
+        read_header(&mut BufReader::new(File::open(path).unwrap())).unwrap()
+    }
+
+    #[test]
+    fn round_trip_keeps_the_value() {
+        let path = temp_dir("round_trip").join("value.data");
+        let value = vec![String::from("173"), String::from("ru-1234")];
+
+        write(&path, &value).unwrap();
+        let (read_value, created): (Vec<String>, _) = read(&path).unwrap();
+
+        assert_eq!(read_value, value);
+        assert!(created.elapsed().unwrap() < Duration::from_secs(60));
+    }
+
+    #[test]
+    fn writes_the_current_header() {
+        let path = temp_dir("header").join("value.data");
+        write(&path, &42u32).unwrap();
+
+        assert!(fs::read(&path).unwrap().starts_with(MAGIC));
+        let header = header_of(&path);
+        assert_eq!(header.version, FORMAT_VERSION);
+        assert_eq!(
+            header.checksum,
+            Some(crc32fast::hash(&bincode::serialize(&42u32).unwrap()))
+        );
+    }
+
+    #[test]
+    fn migrates_files_without_a_header() {
+        let path = temp_dir("version_0").join("value.data");
+        fs::write(&path, bincode::serialize(&vec![1u8, 2, 3]).unwrap()).unwrap();
+
+        let (value, _): (Vec<u8>, _) = read(&path).unwrap();
+
+        assert_eq!(value, [1, 2, 3]);
+        assert_eq!(header_of(&path).version, FORMAT_VERSION);
+    }
+
+    #[test]
+    fn migrates_files_without_a_checksum() {
+        let path = temp_dir("version_1").join("value.data");
+        let mut file = MAGIC.to_vec();
+        file.extend(bincode::serialize(&1u16).unwrap());
+        file.extend(bincode::serialize(&1_000u64).unwrap());
+        file.extend(bincode::serialize(&7u32).unwrap());
+        fs::write(&path, file).unwrap();
+
+        let (value, created): (u32, _) = read(&path).unwrap();
+
+        assert_eq!(value, 7);
+        assert_eq!(created, UNIX_EPOCH + Duration::from_secs(1_000));
+        assert_eq!(
+            header_of(&path).checksum,
+            Some(crc32fast::hash(&7u32.to_le_bytes()))
+        );
+    }
+
+    #[test]
+    fn rejects_newer_versions() {
+        let path = temp_dir("newer").join("value.data");
+        let mut file = MAGIC.to_vec();
+        file.extend(bincode::serialize(&(FORMAT_VERSION + 1)).unwrap());
+        file.extend(bincode::serialize(&0u64).unwrap());
+        file.extend(bincode::serialize(&0u32).unwrap());
+        fs::write(&path, file).unwrap();
+
+        let result: Result<(u32, _), _> = read(&path);
+
+        assert!(
+            matches!(result, Err(CacheError::UnsupportedVersion(v)) if v == FORMAT_VERSION + 1)
+        );
+    }
+
+    #[test]
+    fn rejects_a_wrong_checksum() {
+        let path = temp_dir("checksum").join("value.data");
+        write(&path, &7u32).unwrap();
+
+        let mut file = fs::read(&path).unwrap();
+        *file.last_mut().unwrap() ^= 1;
+        fs::write(&path, file).unwrap();
+
+        let result: Result<(u32, _), _> = read(&path);
+        assert!(matches!(result, Err(CacheError::Corrupted)));
+    }
+
+    #[test]
+    fn missing_file_is_not_cached() {
+        let path = temp_dir("missing").join("value.data");
+
+        let result: Result<(u32, _), _> = read(&path);
+        assert!(matches!(result, Err(CacheError::FileCacheNotExists)));
+    }
+
+    #[test]
+    fn write_replaces_the_file_without_leftovers() {
+        let dir = temp_dir("replace");
+        let path = dir.join("value.data");
+
+        write(&path, &String::from("old")).unwrap();
+        write(&path, &String::from("new")).unwrap();
+
+        let (value, _): (String, _) = read(&path).unwrap();
+        assert_eq!(value, "new");
+        assert!(!path.with_extension("tmp").exists());
+        assert!(path.with_extension("lock").exists());
+    }
+
+    #[test]
+    fn interrupted_write_keeps_the_old_file() {
+        let path = temp_dir("interrupted").join("value.data");
+        write(&path, &String::from("old")).unwrap();
+
+        // A crash before the rename leaves only the temporary file behind
+        fs::write(path.with_extension("tmp"), b"partial").unwrap();
+
+        let (value, _): (String, _) = read(&path).unwrap();
+        assert_eq!(value, "old");
+    }
+
+    #[test]
+    fn read_waits_for_the_writer() {
+        let path = temp_dir("locked").join("value.data");
+        write(&path, &1u32).unwrap();
+
+        let lock = Lock::exclusive(&path).unwrap();
+        let reader = {
+            let path = path.clone();
+            std::thread::spawn(move || read::<u32>(&path).map(|(v, _)| v).ok())
+        };
+
+        std::thread::sleep(Duration::from_millis(100));
+        assert!(!reader.is_finished());
+        drop(lock);
+        assert_eq!(reader.join().unwrap(), Some(1));
     }
 }
@@ -0,0 +1,254 @@
+use core::fmt;
+use std::path::PathBuf;
+
+use crate::locale::{fill, messages};
+
+/// What to do instead of starting the interface
+#[derive(Debug, Clone, PartialEq)]
+pub enum Subcommand {
+    Debug,
+    Help,
+    Cache(CacheCommand),
+    /// Loads every article into the store to read without network
+    Mirror,
+    /// Writes the store into an archive, with the favorites when `favorites` is set
+    Export {
+        path: PathBuf,
+        favorites: bool,
+    },
+    /// Merges an archive into the store
+    Import(PathBuf),
+}
+
+/// What `cache` does, it shows the stats by default
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CacheCommand {
+    Stats,
+    /// Every article in the store with its sizes, revisions and age
+    List,
+    /// Removes the expired articles
+    Prune,
+    /// Removes the objects and every article
+    Clear,
+}
+
+impl CacheCommand {
+    fn from_name(name: &str) -> Option<CacheCommand> {
+        match name {
+            "stats" => Some(CacheCommand::Stats),
+            "list" => Some(CacheCommand::List),
+            "prune" => Some(CacheCommand::Prune),
+            "clear" => Some(CacheCommand::Clear),
+            _ => None,
+        }
+    }
+}
+
+/// Arguments of the binary, every path overrides the XDG one
+#[derive(Debug, Default, PartialEq)]
+pub struct Cli {
+    pub config: Option<PathBuf>,
+    pub data_dir: Option<PathBuf>,
+    pub cache_dir: Option<PathBuf>,
+    pub subcommand: Option<Subcommand>,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum CliError {
+    MissingValue(String),
+    Unknown(String),
+}
+
+impl std::error::Error for CliError {}
+
+impl fmt::Display for CliError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CliError::MissingValue(flag) => write!(f, "{}", fill(messages().missing_path, &[flag])),
+            CliError::Unknown(arg) => write!(f, "{}", fill(messages().unknown_argument, &[arg])),
+        }
+    }
+}
+
+impl Cli {
+    /// Parses the arguments without the name of the binary
+    pub fn parse(args: impl IntoIterator<Item = String>) -> Result<Cli, CliError> {
+        let mut cli = Cli::default();
+        let mut args = args.into_iter();
+        // May go before `export` as any other flag
+        let mut favorites = false;
+
+        while let Some(arg) = args.next() {
+            // Both `--flag value` and `--flag=value` are accepted
+            let (flag, value) = match arg.split_once('=') {
+                Some((flag, value)) if flag.starts_with("--") => {
+                    (flag.to_string(), Some(value.to_string()))
+                }
+                _ => (arg.clone(), None),
+            };
+
+            let mut path = || {
+                value
+                    .clone()
+                    .or_else(|| args.next())
+                    .map(PathBuf::from)
+                    .ok_or_else(|| CliError::MissingValue(flag.clone()))
+            };
+
+            match flag.as_str() {
+                "--config" => cli.config = Some(path()?),
+                "--data-dir" => cli.data_dir = Some(path()?),
+                "--cache-dir" => cli.cache_dir = Some(path()?),
+                "-h" | "--help" => cli.subcommand = Some(Subcommand::Help),
+                "debug" => cli.subcommand = Some(Subcommand::Debug),
+                "cache" => cli.subcommand = Some(Subcommand::Cache(CacheCommand::Stats)),
+                "mirror" => cli.subcommand = Some(Subcommand::Mirror),
+                "export" => {
+                    cli.subcommand = Some(Subcommand::Export {
+                        path: path()?,
+                        favorites: false,
+                    })
+                }
+                "import" => cli.subcommand = Some(Subcommand::Import(path()?)),
+                "--favorites" => favorites = true,
+                name if matches!(cli.subcommand, Some(Subcommand::Cache(_))) => {
+                    let command = CacheCommand::from_name(name)
+                        .ok_or_else(|| CliError::Unknown(arg.clone()))?;
+                    cli.subcommand = Some(Subcommand::Cache(command));
+                }
+                _ => return Err(CliError::Unknown(arg)),
+            }
+        }
+
+        match &mut cli.subcommand {
+            Some(Subcommand::Export { favorites: f, .. }) => *f = favorites,
+            _ if favorites => return Err(CliError::Unknown(String::from("--favorites"))),
+            _ => {}
+        }
+
+        Ok(cli)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Result<Cli, CliError> {
+        Cli::parse(args.iter().map(|a| a.to_string()))
+    }
+
+    #[test]
+    fn no_arguments_start_the_interface() {
+        assert_eq!(parse(&[]), Ok(Cli::default()));
+    }
+
+    #[test]
+    fn parses_paths_in_both_forms() {
+        let cli = parse(&["--config", "a.toml", "--data-dir=data", "--cache-dir", "c"]).unwrap();
+
+        assert_eq!(cli.config, Some(PathBuf::from("a.toml")));
+        assert_eq!(cli.data_dir, Some(PathBuf::from("data")));
+        assert_eq!(cli.cache_dir, Some(PathBuf::from("c")));
+        assert_eq!(cli.subcommand, None);
+    }
+
+    #[test]
+    fn keeps_equal_signs_in_paths() {
+        let cli = parse(&["--config=a=b.toml"]).unwrap();
+
+        assert_eq!(cli.config, Some(PathBuf::from("a=b.toml")));
+    }
+
+    #[test]
+    fn paths_need_a_value() {
+        assert_eq!(
+            parse(&["--config"]),
+            Err(CliError::MissingValue(String::from("--config")))
+        );
+        assert_eq!(
+            parse(&["export"]),
+            Err(CliError::MissingValue(String::from("export")))
+        );
+    }
+
+    #[test]
+    fn parses_subcommands() {
+        assert_eq!(parse(&["-h"]).unwrap().subcommand, Some(Subcommand::Help));
+        assert_eq!(
+            parse(&["--help"]).unwrap().subcommand,
+            Some(Subcommand::Help)
+        );
+        assert_eq!(
+            parse(&["debug"]).unwrap().subcommand,
+            Some(Subcommand::Debug)
+        );
+        assert_eq!(
+            parse(&["mirror"]).unwrap().subcommand,
+            Some(Subcommand::Mirror)
+        );
+        assert_eq!(
+            parse(&["import", "a.scpa"]).unwrap().subcommand,
+            Some(Subcommand::Import(PathBuf::from("a.scpa")))
+        );
+    }
+
+    #[test]
+    fn parses_cache_commands() {
+        assert_eq!(
+            parse(&["cache"]).unwrap().subcommand,
+            Some(Subcommand::Cache(CacheCommand::Stats))
+        );
+        assert_eq!(
+            parse(&["cache", "prune"]).unwrap().subcommand,
+            Some(Subcommand::Cache(CacheCommand::Prune))
+        );
+        assert_eq!(
+            parse(&["cache", "wipe"]),
+            Err(CliError::Unknown(String::from("wipe")))
+        );
+    }
+
+    #[test]
+    fn favorites_go_with_export_only() {
+        let export = |favorites| {
+            Some(Subcommand::Export {
+                path: PathBuf::from("a.scpa"),
+                favorites,
+            })
+        };
+
+        assert_eq!(
+            parse(&["export", "a.scpa"]).unwrap().subcommand,
+            export(false)
+        );
+        assert_eq!(
+            parse(&["--favorites", "export", "a.scpa"])
+                .unwrap()
+                .subcommand,
+            export(true)
+        );
+        assert_eq!(
+            parse(&["export", "a.scpa", "--favorites"])
+                .unwrap()
+                .subcommand,
+            export(true)
+        );
+        assert_eq!(
+            parse(&["mirror", "--favorites"]),
+            Err(CliError::Unknown(String::from("--favorites")))
+        );
+    }
+
+    #[test]
+    fn rejects_unknown_arguments() {
+        assert_eq!(
+            parse(&["--verbose"]),
+            Err(CliError::Unknown(String::from("--verbose")))
+        );
+        assert_eq!(
+            parse(&["start"]),
+            Err(CliError::Unknown(String::from("start")))
+        );
+    }
+}
@@ -0,0 +1,121 @@
+use core::fmt;
+
+use crate::locale::{fill, messages};
+
+/// Commands typed after `:` in the command prompt
+#[derive(Debug, PartialEq)]
+pub enum Command {
+    /// Id of an object as used in the catalog and the API, e.g. `002` or `ru-1234`
+    Jump(String),
+}
+
+#[derive(Debug, PartialEq)]
+pub enum CommandError {
+    Empty,
+    Unknown(String),
+}
+
+impl std::error::Error for CommandError {}
+
+impl fmt::Display for CommandError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CommandError::Empty => write!(f, "{}", messages().empty_command),
+            CommandError::Unknown(c) => {
+                write!(f, "{}", fill(messages().unknown_command, &[c]))
+            }
+        }
+    }
+}
+
+impl Command {
+    /**
+    Parses the text of the prompt. A number, optionally with the `SCP-`
+    prefix or a branch like `RU-`, jumps to that object.
+
+    ## Example
+
+    `2316`, `scp-173`, `RU-1234`, `SCP-RU-1234`
+    */
+    pub fn parse(s: &str) -> Result<Command, CommandError> {
+        let s = s.trim();
+        if s.is_empty() {
+            return Err(CommandError::Empty);
+        }
+
+        let lower = s.to_lowercase();
+        let target = lower.strip_prefix("scp-").unwrap_or(&lower);
+
+        let (branch, number) = match target.rsplit_once('-') {
+            Some((branch, number)) => (Some(branch), number),
+            None => (None, target),
+        };
+
+        let valid_branch =
+            branch.is_none_or(|b| !b.is_empty() && b.chars().all(|c| c.is_ascii_alphabetic()));
+
+        match number.parse::<u32>() {
+            Ok(n) if valid_branch => {
+                // Objects of the main series have at least three digits
+                let number = format!("{:03}", n);
+                match branch {
+                    Some(branch) => Ok(Command::Jump(format!("{}-{}", branch, number))),
+                    None => Ok(Command::Jump(number)),
+                }
+            }
+            _ => Err(CommandError::Unknown(s.to_string())),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn jump(id: &str) -> Result<Command, CommandError> {
+        Ok(Command::Jump(id.to_string()))
+    }
+
+    #[test]
+    fn parses_numbers() {
+        assert_eq!(Command::parse("2316"), jump("2316"));
+        assert_eq!(Command::parse("  173 "), jump("173"));
+    }
+
+    #[test]
+    fn pads_short_numbers() {
+        assert_eq!(Command::parse("2"), jump("002"));
+        assert_eq!(Command::parse("scp-02"), jump("002"));
+    }
+
+    #[test]
+    fn strips_the_scp_prefix_in_any_case() {
+        assert_eq!(Command::parse("scp-173"), jump("173"));
+        assert_eq!(Command::parse("SCP-173"), jump("173"));
+    }
+
+    #[test]
+    fn keeps_the_branch() {
+        assert_eq!(Command::parse("RU-1234"), jump("ru-1234"));
+        assert_eq!(Command::parse("SCP-RU-1234"), jump("ru-1234"));
+        assert_eq!(Command::parse("ru-12"), jump("ru-012"));
+    }
+
+    #[test]
+    fn rejects_empty_input() {
+        assert_eq!(Command::parse(""), Err(CommandError::Empty));
+        assert_eq!(Command::parse("   "), Err(CommandError::Empty));
+    }
+
+    #[test]
+    fn rejects_anything_else() {
+        for s in ["scp-", "-173", "r2-173", "ru--173", "173a", "keter"] {
+            assert_eq!(
+                Command::parse(s),
+                Err(CommandError::Unknown(s.to_string())),
+                "{}",
+                s
+            );
+        }
+    }
+}
@@ -0,0 +1,31 @@
+use std::io;
+
+/// Level of zstd, the higher ones are much slower for a few percent
+const LEVEL: i32 = 9;
+/// Upper bound of a dictionary trained from the cached articles
+const DICTIONARY_SIZE: usize = 112 * 1024;
+
+/// Compresses with the shared dictionary when there is one
+pub fn compress(data: &[u8], dictionary: Option<&[u8]>) -> io::Result<Vec<u8>> {
+    match dictionary {
+        Some(d) => zstd::bulk::Compressor::with_dictionary(LEVEL, d)?.compress(data),
+        None => zstd::bulk::compress(data, LEVEL),
+    }
+}
+
+/// `raw_size` is the length of the data before it was compressed
+pub fn decompress(data: &[u8], raw_size: usize, dictionary: Option<&[u8]>) -> io::Result<Vec<u8>> {
+    match dictionary {
+        Some(d) => zstd::bulk::Decompressor::with_dictionary(d)?.decompress(data, raw_size),
+        None => zstd::bulk::decompress(data, raw_size),
+    }
+}
+
+/**
+Builds a dictionary from the given articles. Articles of the site share most
+of their markup, so with it even a short article takes a few hundred bytes.
+Fails when there are too few samples to learn from.
+*/
+pub fn train<S: AsRef<[u8]>>(samples: &[S]) -> io::Result<Vec<u8>> {
+    zstd::dict::from_samples(samples, DICTIONARY_SIZE)
+}
@@ -0,0 +1,153 @@
+use core::fmt;
+use std::{collections::HashMap, fs, io::ErrorKind, path::Path, time::Duration};
+
+use serde::Deserialize;
+
+use crate::{
+    keymap::{Action, Keymap, KeymapError, Preset},
+    locale::{fill, messages, Locale},
+    parsing::Site,
+    paths,
+    theme::{Theme, ThemeError, ThemeName},
+};
+
+/**
+Read from `$XDG_CONFIG_HOME/scpfoundation-explorer/config.toml` or the file
+given with `--config`.
+
+## Example
+
+```toml
+wrap_around = false
+keymap_preset = "vim"
+reading_width = 80
+theme = "high_contrast"
+locale = "en"
+base_url = "https://scpfoundation.net"
+branch = "scp-series"
+concurrency = 4
+cache_ttl = 168
+article_ttl = 24
+mirror_delay = 500
+
+[keymap]
+refresh = ["ctrl+r", "f5"]
+first = ["g g", "home"]
+quit = ["esc", "ctrl+q"]
+
+[colors]
+focus = "blue"
+keter = "#ff5f5f"
+```
+*/
+#[derive(Debug, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    /// Whether moving past the last item of a list goes to the first one
+    pub wrap_around: bool,
+    /// Bindings the keymap starts from, `default` or `vim`
+    pub keymap_preset: Preset,
+    /// Keys for every action which bindings should differ from the defaults
+    pub keymap: HashMap<Action, Vec<String>>,
+    /// Longest line of an article in columns, `0` for the whole explorer
+    pub reading_width: u16,
+    /// `default`, `high_contrast` or `monochrome`
+    pub theme: ThemeName,
+    /// Colors replacing the ones of the theme, see [`Theme::with_colors`]
+    pub colors: HashMap<String, String>,
+    /// `ru` or `en`, taken from `LC_ALL`, `LC_MESSAGES` or `LANG` when unset
+    pub locale: Option<Locale>,
+    /// Site the objects and the articles are scraped from
+    pub base_url: String,
+    /// Page of the first series, see [`Site::branch`]
+    pub branch: String,
+    /// How many pages are scraped at once
+    pub concurrency: usize,
+    /// Hours before the objects are scraped again, `0` keeps them forever
+    pub cache_ttl: u64,
+    /// Hours before a cached article is loaded again, `0` keeps it forever
+    pub article_ttl: u64,
+    /// Milliseconds between two articles loaded by the `mirror` command
+    pub mirror_delay: u64,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        let site = Site::default();
+
+        Config {
+            wrap_around: true,
+            keymap_preset: Preset::default(),
+            keymap: HashMap::new(),
+            reading_width: 100,
+            theme: ThemeName::default(),
+            colors: HashMap::new(),
+            locale: None,
+            base_url: site.base_url,
+            branch: site.branch,
+            concurrency: site.concurrency,
+            cache_ttl: 24 * 7,
+            article_ttl: 24,
+            mirror_delay: site.mirror_delay.as_millis() as u64,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum ConfigError {
+    Read(std::io::Error),
+    Parse(toml::de::Error),
+    Keymap(KeymapError),
+    Theme(ThemeError),
+}
+
+impl std::error::Error for ConfigError {}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let path = paths::config_file().display();
+        let (message, e): (_, &dyn fmt::Display) = match self {
+            ConfigError::Read(e) => (messages().config_read_failed, e),
+            ConfigError::Parse(e) => (messages().config_parse_failed, e),
+            ConfigError::Keymap(e) => (messages().config_keymap, e),
+            ConfigError::Theme(e) => (messages().config_theme, e),
+        };
+        write!(f, "{}", fill(message, &[&path, e]))
+    }
+}
+
+impl Config {
+    /// Reads the config at `path`, a missing file means defaults.
+    pub fn load(path: &Path) -> Result<Config, ConfigError> {
+        match fs::read_to_string(path) {
+            Ok(s) => toml::from_str(&s).map_err(ConfigError::Parse),
+            Err(e) if e.kind() == ErrorKind::NotFound => Ok(Config::default()),
+            Err(e) => Err(ConfigError::Read(e)),
+        }
+    }
+
+    pub fn keymap(&self) -> Result<Keymap, ConfigError> {
+        Keymap::with_overrides(self.keymap_preset, &self.keymap).map_err(ConfigError::Keymap)
+    }
+
+    pub fn site(&self) -> Site {
+        Site {
+            base_url: self.base_url.clone(),
+            branch: self.branch.clone(),
+            concurrency: self.concurrency,
+            mirror_delay: Duration::from_millis(self.mirror_delay),
+        }
+    }
+
+    pub fn cache_ttl(&self) -> Duration {
+        Duration::from_secs(self.cache_ttl * 60 * 60)
+    }
+
+    pub fn article_ttl(&self) -> Duration {
+        Duration::from_secs(self.article_ttl * 60 * 60)
+    }
+
+    pub fn theme(&self) -> Result<Theme, ConfigError> {
+        Theme::with_colors(self.theme, &self.colors).map_err(ConfigError::Theme)
+    }
+}
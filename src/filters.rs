@@ -0,0 +1,197 @@
+use core::fmt;
+use std::{cmp::Ordering, collections::HashMap};
+
+use crate::{
+    locale,
+    parsing::{ClassificationScp, ScpObject},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SortBy {
+    /// Order the objects were scraped in
+    #[default]
+    Scrape,
+    Number,
+    Title,
+    Class,
+    /// Only objects which articles were opened have a rating
+    Rating,
+}
+
+impl SortBy {
+    pub fn next(self) -> Self {
+        match self {
+            SortBy::Scrape => SortBy::Number,
+            SortBy::Number => SortBy::Title,
+            SortBy::Title => SortBy::Class,
+            SortBy::Class => SortBy::Rating,
+            SortBy::Rating => SortBy::Scrape,
+        }
+    }
+}
+
+impl fmt::Display for SortBy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", locale::sort_name(*self))
+    }
+}
+
+/// Class filters and sorting applied to the objects list on top of the search
+#[derive(Debug, Clone, Default)]
+pub struct Filters {
+    classes: Vec<ClassificationScp>,
+    pub sort: SortBy,
+    pub reverse: bool,
+}
+
+impl Filters {
+    pub fn toggle_class(&mut self, class: ClassificationScp) {
+        match self.classes.iter().position(|c| *c == class) {
+            Some(i) => {
+                self.classes.remove(i);
+            }
+            None => self.classes.push(class),
+        }
+    }
+
+    pub fn clear(&mut self) {
+        *self = Filters::default();
+    }
+
+    pub fn matches(&self, object: &ScpObject) -> bool {
+        self.classes.is_empty() || self.classes.contains(object.get_class())
+    }
+
+    /// Sorts indices of `objects` in place, so the objects are never copied
+    pub fn sort(&self, view: &mut [usize], objects: &[ScpObject], ratings: &HashMap<String, i32>) {
+        let by_number = |a: &ScpObject, b: &ScpObject| match (
+            a.get_id().parse::<u32>(),
+            b.get_id().parse::<u32>(),
+        ) {
+            (Ok(a), Ok(b)) => a.cmp(&b),
+            (Ok(_), Err(_)) => Ordering::Less,
+            (Err(_), Ok(_)) => Ordering::Greater,
+            (Err(_), Err(_)) => a.get_id().cmp(&b.get_id()),
+        };
+
+        match self.sort {
+            SortBy::Scrape => {}
+            SortBy::Number => view.sort_by(|a, b| by_number(&objects[*a], &objects[*b])),
+            SortBy::Title => view.sort_by_cached_key(|i| objects[*i].get_name().to_lowercase()),
+            SortBy::Class => view.sort_by_key(|i| *objects[*i].get_class()),
+            SortBy::Rating => view.sort_by(|a, b| {
+                // Known ratings first, the best on top
+                let a = ratings.get(&objects[*a].get_id().to_lowercase());
+                let b = ratings.get(&objects[*b].get_id().to_lowercase());
+                match (a, b) {
+                    (Some(a), Some(b)) => b.cmp(a),
+                    (Some(_), None) => Ordering::Less,
+                    (None, Some(_)) => Ordering::Greater,
+                    (None, None) => Ordering::Equal,
+                }
+            }),
+        }
+
+        if self.reverse {
+            view.reverse();
+        }
+    }
+
+    /// Short description for the title of the objects block, empty by default
+    pub fn describe(&self) -> String {
+        let mut parts: Vec<String> = Vec::new();
+
+        if !self.classes.is_empty() {
+            let classes: Vec<String> = self.classes.iter().map(|c| c.to_string()).collect();
+            parts.push(format!("[{}]", classes.join(", ")));
+        }
+
+        if self.sort != SortBy::Scrape || self.reverse {
+            let arrow = if self.reverse { "↑" } else { "↓" };
+            parts.push(format!("{} {}", arrow, self.sort));
+        }
+
+        parts.join(" ")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn objects(ids: &[&str]) -> Vec<ScpObject> {
+        ids.iter()
+            .map(|id| {
+                ScpObject::new(
+                    ClassificationScp::Safe,
+                    format!("Object {}", id),
+                    id.to_string(),
+                )
+            })
+            .collect()
+    }
+
+    /// Ids of `objects` in the order `filters` sort them
+    fn sorted(
+        filters: &Filters,
+        objects: &[ScpObject],
+        ratings: &HashMap<String, i32>,
+    ) -> Vec<String> {
+        let mut view: Vec<usize> = (0..objects.len()).collect();
+        filters.sort(&mut view, objects, ratings);
+        view.iter().map(|i| objects[*i].get_id()).collect()
+    }
+
+    #[test]
+    fn sorts_numbers_before_other_ids() {
+        let objects = objects(&["RU-1234", "173", "J", "002", "1000"]);
+        let filters = Filters {
+            sort: SortBy::Number,
+            ..Filters::default()
+        };
+
+        assert_eq!(
+            sorted(&filters, &objects, &HashMap::new()),
+            ["002", "173", "1000", "J", "RU-1234"]
+        );
+    }
+
+    #[test]
+    fn sorts_unknown_ratings_last() {
+        let objects = objects(&["002", "173", "RU-1234", "096"]);
+        let ratings = HashMap::from([
+            ("173".to_string(), 50),
+            ("ru-1234".to_string(), 120),
+            ("096".to_string(), -3),
+        ]);
+        let filters = Filters {
+            sort: SortBy::Rating,
+            ..Filters::default()
+        };
+
+        assert_eq!(
+            sorted(&filters, &objects, &ratings),
+            ["RU-1234", "173", "096", "002"]
+        );
+    }
+
+    #[test]
+    fn reverse_flips_the_order() {
+        let objects = objects(&["173", "002", "J"]);
+        let mut filters = Filters {
+            reverse: true,
+            ..Filters::default()
+        };
+
+        assert_eq!(
+            sorted(&filters, &objects, &HashMap::new()),
+            ["J", "002", "173"]
+        );
+
+        filters.sort = SortBy::Number;
+        assert_eq!(
+            sorted(&filters, &objects, &HashMap::new()),
+            ["J", "173", "002"]
+        );
+    }
+}
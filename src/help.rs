@@ -0,0 +1,86 @@
+use crate::{
+    keymap::{Action, Keymap},
+    locale::messages,
+};
+
+/// Group of rows in the help overlay, each row is the keys and what they do
+pub struct Section {
+    pub title: String,
+    pub rows: Vec<(String, &'static str)>,
+}
+
+/// Every key of the action, `None` if it is unbound
+pub fn keys(keymap: &Keymap, action: Action) -> Option<String> {
+    let keys: Vec<String> = keymap.keys(action).iter().map(|k| k.to_string()).collect();
+
+    if keys.is_empty() {
+        None
+    } else {
+        Some(keys.join(", "))
+    }
+}
+
+fn rows<'a>(
+    keymap: &Keymap,
+    actions: impl Iterator<Item = &'a Action>,
+) -> Vec<(String, &'static str)> {
+    actions
+        .filter_map(|a| Some((keys(keymap, *a)?, a.description())))
+        .collect()
+}
+
+/**
+Builds the help from the active keymap. Actions of the focused pane go
+first under `context`, then every other bound action, the keys of text
+prompts and the mouse.
+*/
+pub fn sections(keymap: &Keymap, context: &str, actions: &[Action]) -> Vec<Section> {
+    let m = messages();
+    let mut input = vec![
+        (String::from("Enter"), m.hint_confirm),
+        (String::from("Esc"), m.hint_cancel),
+        (String::from("Backspace"), m.erase_char),
+    ];
+    if !keymap.is_modal() {
+        input.push((String::from(m.any_char), m.start_search));
+    }
+
+    vec![
+        Section {
+            title: context.to_string(),
+            rows: rows(keymap, actions.iter()),
+        },
+        Section {
+            title: String::from(m.all_actions),
+            rows: rows(keymap, Action::ALL.iter().filter(|a| !actions.contains(a))),
+        },
+        Section {
+            title: String::from(m.text_input),
+            rows: input,
+        },
+        Section {
+            title: String::from(m.mouse),
+            rows: vec![
+                (String::from(m.click), m.click_action),
+                (String::from(m.click_selected), m.hint_open),
+                (String::from(m.wheel), m.scrolling),
+            ],
+        },
+    ]
+}
+
+/// Scroll position of the overlay, the size is updated on every render
+#[derive(Clone, Default)]
+pub struct Overlay {
+    pub scroll: usize,
+    pub viewport: usize,
+    pub len: usize,
+}
+
+impl Overlay {
+    /// Scrolls by `delta` lines, never past the last screen of text
+    pub fn scroll_by(&mut self, delta: isize) {
+        let max = self.len.saturating_sub(self.viewport);
+        self.scroll = self.scroll.saturating_add_signed(delta).min(max);
+    }
+}
@@ -0,0 +1,762 @@
+use core::fmt;
+use std::collections::HashMap;
+
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use serde::Deserialize;
+
+use crate::locale::{self, fill};
+
+/// Everything the user can trigger from the keyboard outside of text input.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Action {
+    Quit,
+    NextWindow,
+    PreviousWindow,
+    Up,
+    Down,
+    PageUp,
+    PageDown,
+    HalfPageUp,
+    HalfPageDown,
+    First,
+    Last,
+    Open,
+    Refresh,
+    SaveSearch,
+    DeleteSearch,
+    FilterSafe,
+    FilterEuclid,
+    FilterKeter,
+    FilterThaumiel,
+    FilterNeutralized,
+    FilterNonStandard,
+    FilterNone,
+    ClearFilters,
+    CycleSort,
+    ReverseSort,
+    Command,
+    Palette,
+    Find,
+    NextMatch,
+    PreviousMatch,
+    Close,
+    Search,
+    NextLink,
+    PreviousLink,
+    ScrollLeft,
+    ScrollRight,
+    ToggleWrap,
+    ToggleZen,
+    OpenInNewTab,
+    NextTab,
+    PreviousTab,
+    Compare,
+    ToggleSyncScroll,
+    ToggleFavorite,
+    ToggleTheme,
+    SwitchBranch,
+    Export,
+    Help,
+}
+
+impl Action {
+    /// Every action in the order they are listed in the command palette
+    pub const ALL: [Action; 48] = [
+        Action::Open,
+        Action::OpenInNewTab,
+        Action::Close,
+        Action::NextTab,
+        Action::PreviousTab,
+        Action::Compare,
+        Action::ToggleSyncScroll,
+        Action::ToggleFavorite,
+        Action::NextLink,
+        Action::PreviousLink,
+        Action::ToggleWrap,
+        Action::ToggleZen,
+        Action::ScrollLeft,
+        Action::ScrollRight,
+        Action::Search,
+        Action::Find,
+        Action::NextMatch,
+        Action::PreviousMatch,
+        Action::Command,
+        Action::Refresh,
+        Action::SwitchBranch,
+        Action::Export,
+        Action::ToggleTheme,
+        Action::SaveSearch,
+        Action::DeleteSearch,
+        Action::FilterSafe,
+        Action::FilterEuclid,
+        Action::FilterKeter,
+        Action::FilterThaumiel,
+        Action::FilterNeutralized,
+        Action::FilterNonStandard,
+        Action::FilterNone,
+        Action::ClearFilters,
+        Action::CycleSort,
+        Action::ReverseSort,
+        Action::NextWindow,
+        Action::PreviousWindow,
+        Action::Up,
+        Action::Down,
+        Action::PageUp,
+        Action::PageDown,
+        Action::HalfPageUp,
+        Action::HalfPageDown,
+        Action::First,
+        Action::Last,
+        Action::Palette,
+        Action::Help,
+        Action::Quit,
+    ];
+
+    /// Description in the current locale
+    pub fn description(&self) -> &'static str {
+        locale::action_description(*self)
+    }
+}
+
+/**
+Pairs of letters sitting on the same physical key in the Russian (ЙЦУКЕН)
+and the English (QWERTY) layouts. Bindings are always stored in their latin
+form, so a shortcut keeps working whatever layout is active. Russian letters
+on the punctuation keys aren't mapped: they would collide with punctuation
+bound on its own, and typing them would trigger it instead of a search.
+*/
+const LAYOUT_RU: [(char, char); 52] = [
+    ('й', 'q'),
+    ('ц', 'w'),
+    ('у', 'e'),
+    ('к', 'r'),
+    ('е', 't'),
+    ('н', 'y'),
+    ('г', 'u'),
+    ('ш', 'i'),
+    ('щ', 'o'),
+    ('з', 'p'),
+    ('ф', 'a'),
+    ('ы', 's'),
+    ('в', 'd'),
+    ('а', 'f'),
+    ('п', 'g'),
+    ('р', 'h'),
+    ('о', 'j'),
+    ('л', 'k'),
+    ('д', 'l'),
+    ('я', 'z'),
+    ('ч', 'x'),
+    ('с', 'c'),
+    ('м', 'v'),
+    ('и', 'b'),
+    ('т', 'n'),
+    ('ь', 'm'),
+    // Shift gives the uppercase letters on both layouts
+    ('Й', 'Q'),
+    ('Ц', 'W'),
+    ('У', 'E'),
+    ('К', 'R'),
+    ('Е', 'T'),
+    ('Н', 'Y'),
+    ('Г', 'U'),
+    ('Ш', 'I'),
+    ('Щ', 'O'),
+    ('З', 'P'),
+    ('Ф', 'A'),
+    ('Ы', 'S'),
+    ('В', 'D'),
+    ('А', 'F'),
+    ('П', 'G'),
+    ('Р', 'H'),
+    ('О', 'J'),
+    ('Л', 'K'),
+    ('Д', 'L'),
+    ('Я', 'Z'),
+    ('Ч', 'X'),
+    ('С', 'C'),
+    ('М', 'V'),
+    ('И', 'B'),
+    ('Т', 'N'),
+    ('Ь', 'M'),
+];
+
+fn to_latin(c: char) -> char {
+    LAYOUT_RU
+        .iter()
+        .find(|(ru, _)| *ru == c)
+        .map_or(c, |(_, en)| *en)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct KeyBinding {
+    code: KeyCode,
+    modifiers: KeyModifiers,
+}
+
+impl KeyBinding {
+    pub fn new(code: KeyCode, modifiers: KeyModifiers) -> Self {
+        KeyBinding { code, modifiers }.normalize()
+    }
+
+    /// Brings a key to the form bindings are stored in: latin letters and no
+    /// explicit `Shift` for characters, as it is already part of the char.
+    fn normalize(mut self) -> Self {
+        if let KeyCode::Char(c) = self.code {
+            self.code = KeyCode::Char(to_latin(c));
+            self.modifiers.remove(KeyModifiers::SHIFT);
+        }
+
+        // Terminals send `Shift+Tab` as `BackTab`, reported together with `Shift`
+        if self.code == KeyCode::Tab && self.modifiers.contains(KeyModifiers::SHIFT) {
+            self.code = KeyCode::BackTab;
+        }
+        if self.code == KeyCode::BackTab {
+            self.modifiers.remove(KeyModifiers::SHIFT);
+        }
+
+        self
+    }
+
+    /// Parses strings like `ctrl+r`, `shift+tab`, `pagedown` or `?`.
+    pub fn parse(s: &str) -> Result<Self, KeymapError> {
+        let mut modifiers = KeyModifiers::NONE;
+        let mut parts: Vec<&str> = s.split('+').collect();
+
+        // `+` itself and bindings such as `ctrl++`
+        if s.ends_with("++") || s == "+" {
+            parts.pop();
+            parts.pop();
+            parts.push("+");
+        }
+
+        let key = parts.pop().unwrap_or_default();
+
+        for m in parts {
+            match m.to_lowercase().as_str() {
+                "ctrl" | "control" => modifiers.insert(KeyModifiers::CONTROL),
+                "alt" => modifiers.insert(KeyModifiers::ALT),
+                "shift" => modifiers.insert(KeyModifiers::SHIFT),
+                _ => return Err(KeymapError::UnknownKey(s.to_string())),
+            }
+        }
+
+        let code = match key.to_lowercase().as_str() {
+            "esc" | "escape" => KeyCode::Esc,
+            "enter" | "return" => KeyCode::Enter,
+            "left" => KeyCode::Left,
+            "right" => KeyCode::Right,
+            "up" => KeyCode::Up,
+            "down" => KeyCode::Down,
+            "pageup" | "pgup" => KeyCode::PageUp,
+            "pagedown" | "pgdown" => KeyCode::PageDown,
+            "home" => KeyCode::Home,
+            "end" => KeyCode::End,
+            "tab" => KeyCode::Tab,
+            "backtab" => KeyCode::BackTab,
+            "backspace" => KeyCode::Backspace,
+            "delete" | "del" => KeyCode::Delete,
+            "insert" | "ins" => KeyCode::Insert,
+            "space" => KeyCode::Char(' '),
+            f if f.len() > 1 && f.starts_with('f') => match f[1..].parse::<u8>() {
+                Ok(n) if (1..=12).contains(&n) => KeyCode::F(n),
+                _ => return Err(KeymapError::UnknownKey(s.to_string())),
+            },
+            _ => {
+                let mut chars = key.chars();
+                match (chars.next(), chars.next()) {
+                    (Some(c), None) if modifiers.contains(KeyModifiers::SHIFT) => {
+                        KeyCode::Char(c.to_uppercase().next().unwrap_or(c))
+                    }
+                    (Some(c), None) => KeyCode::Char(c),
+                    _ => return Err(KeymapError::UnknownKey(s.to_string())),
+                }
+            }
+        };
+
+        Ok(KeyBinding::new(code, modifiers))
+    }
+}
+
+impl From<&KeyEvent> for KeyBinding {
+    fn from(key: &KeyEvent) -> Self {
+        KeyBinding::new(key.code, key.modifiers)
+    }
+}
+
+impl fmt::Display for KeyBinding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.modifiers.contains(KeyModifiers::CONTROL) {
+            write!(f, "Ctrl+")?;
+        }
+        if self.modifiers.contains(KeyModifiers::ALT) {
+            write!(f, "Alt+")?;
+        }
+        if self.modifiers.contains(KeyModifiers::SHIFT) {
+            write!(f, "Shift+")?;
+        }
+
+        match self.code {
+            KeyCode::Esc => write!(f, "Esc"),
+            KeyCode::Enter => write!(f, "Enter"),
+            KeyCode::Left => write!(f, "←"),
+            KeyCode::Right => write!(f, "→"),
+            KeyCode::Up => write!(f, "↑"),
+            KeyCode::Down => write!(f, "↓"),
+            KeyCode::PageUp => write!(f, "PgUp"),
+            KeyCode::PageDown => write!(f, "PgDown"),
+            KeyCode::Home => write!(f, "Home"),
+            KeyCode::End => write!(f, "End"),
+            KeyCode::Tab => write!(f, "Tab"),
+            KeyCode::BackTab => write!(f, "Shift+Tab"),
+            KeyCode::Backspace => write!(f, "Backspace"),
+            KeyCode::Delete => write!(f, "Del"),
+            KeyCode::Insert => write!(f, "Ins"),
+            KeyCode::F(n) => write!(f, "F{}", n),
+            KeyCode::Char(' ') => write!(f, "Space"),
+            KeyCode::Char(c) if self.modifiers.is_empty() => write!(f, "{}", c),
+            KeyCode::Char(c) => write!(f, "{}", c.to_ascii_uppercase()),
+            _ => write!(f, "?"),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum KeymapError {
+    UnknownKey(String),
+}
+
+impl std::error::Error for KeymapError {}
+
+impl fmt::Display for KeymapError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            KeymapError::UnknownKey(k) => {
+                write!(f, "{}", fill(locale::messages().unknown_key, &[k]))
+            }
+        }
+    }
+}
+
+/// Keys pressed one after another, like `g g` in vim
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct KeySequence(Vec<KeyBinding>);
+
+impl KeySequence {
+    /// Parses keys separated by spaces, each as in [`KeyBinding::parse`]
+    pub fn parse(s: &str) -> Result<Self, KeymapError> {
+        let keys = s
+            .split_whitespace()
+            .map(KeyBinding::parse)
+            .collect::<Result<Vec<KeyBinding>, KeymapError>>()?;
+
+        if keys.is_empty() {
+            return Err(KeymapError::UnknownKey(s.to_string()));
+        }
+
+        Ok(KeySequence(keys))
+    }
+}
+
+impl fmt::Display for KeySequence {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let keys: Vec<String> = self.0.iter().map(|k| k.to_string()).collect();
+        write!(f, "{}", keys.join(" "))
+    }
+}
+
+/// Set of bindings the keymap starts from before the overrides of the config
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Preset {
+    /// Arrows and shortcuts with modifiers, typing letters starts a search
+    #[default]
+    Default,
+    /// Modal vim-like keys, letters are commands and `/` starts a search
+    Vim,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum Lookup {
+    Action(Action),
+    /// The key starts a sequence, wait for the next one
+    Pending,
+    Unbound,
+}
+
+#[derive(Clone)]
+pub struct Keymap {
+    bindings: HashMap<KeySequence, Action>,
+    /// Keys of an unfinished sequence
+    pending: Vec<KeyBinding>,
+    /// In a modal keymap unbound chars do nothing instead of starting a search
+    modal: bool,
+}
+
+impl Default for Keymap {
+    fn default() -> Self {
+        Keymap::with_preset(Preset::Default)
+    }
+}
+
+impl Keymap {
+    pub fn with_preset(preset: Preset) -> Self {
+        let mut keymap = Keymap {
+            bindings: HashMap::new(),
+            pending: Vec::new(),
+            modal: preset == Preset::Vim,
+        };
+
+        let common: [(&str, Action); 49] = [
+            ("left", Action::PreviousWindow),
+            ("right", Action::NextWindow),
+            ("up", Action::Up),
+            ("down", Action::Down),
+            ("pageup", Action::PageUp),
+            ("pagedown", Action::PageDown),
+            ("ctrl+u", Action::HalfPageUp),
+            ("ctrl+d", Action::HalfPageDown),
+            ("home", Action::First),
+            ("end", Action::Last),
+            ("enter", Action::Open),
+            ("ctrl+r", Action::Refresh),
+            ("f5", Action::Refresh),
+            ("ctrl+s", Action::SaveSearch),
+            ("delete", Action::DeleteSearch),
+            ("alt+1", Action::FilterSafe),
+            ("alt+2", Action::FilterEuclid),
+            ("alt+3", Action::FilterKeter),
+            ("alt+4", Action::FilterThaumiel),
+            ("alt+5", Action::FilterNeutralized),
+            ("alt+6", Action::FilterNonStandard),
+            ("alt+7", Action::FilterNone),
+            ("alt+0", Action::ClearFilters),
+            ("ctrl+o", Action::CycleSort),
+            ("alt+o", Action::ReverseSort),
+            (":", Action::Command),
+            ("ctrl+p", Action::Palette),
+            ("/", Action::Find),
+            ("f3", Action::NextMatch),
+            ("shift+f3", Action::PreviousMatch),
+            ("tab", Action::NextLink),
+            ("backtab", Action::PreviousLink),
+            ("shift+left", Action::ScrollLeft),
+            ("shift+right", Action::ScrollRight),
+            ("alt+w", Action::ToggleWrap),
+            ("alt+z", Action::ToggleZen),
+            ("alt+enter", Action::OpenInNewTab),
+            ("ctrl+t", Action::OpenInNewTab),
+            ("ctrl+pagedown", Action::NextTab),
+            ("ctrl+pageup", Action::PreviousTab),
+            ("alt+right", Action::NextTab),
+            ("alt+left", Action::PreviousTab),
+            ("alt+c", Action::Compare),
+            ("alt+s", Action::ToggleSyncScroll),
+            ("alt+f", Action::ToggleFavorite),
+            ("alt+t", Action::ToggleTheme),
+            ("alt+b", Action::SwitchBranch),
+            ("alt+e", Action::Export),
+            ("?", Action::Help),
+        ];
+
+        let preset: &[(&str, Action)] = match preset {
+            Preset::Default => &[("esc", Action::Quit), ("ctrl+w", Action::Close)],
+            Preset::Vim => &[
+                ("j", Action::Down),
+                ("k", Action::Up),
+                ("h", Action::PreviousWindow),
+                ("l", Action::NextWindow),
+                ("g g", Action::First),
+                ("g t", Action::NextTab),
+                ("g shift+t", Action::PreviousTab),
+                ("shift+g", Action::Last),
+                ("ctrl+f", Action::PageDown),
+                ("ctrl+b", Action::PageUp),
+                ("n", Action::NextMatch),
+                ("shift+n", Action::PreviousMatch),
+                ("q", Action::Close),
+                ("s", Action::Search),
+            ],
+        };
+
+        for (keys, action) in common.iter().chain(preset) {
+            keymap.bind(KeySequence::parse(keys).unwrap(), *action);
+        }
+
+        keymap
+    }
+
+    /**
+    Builds the keymap of `preset` and replaces the bindings of every action
+    listed in `overrides` with the given keys.
+    */
+    pub fn with_overrides(
+        preset: Preset,
+        overrides: &HashMap<Action, Vec<String>>,
+    ) -> Result<Self, KeymapError> {
+        let mut keymap = Keymap::with_preset(preset);
+
+        for (action, keys) in overrides {
+            keymap.bindings.retain(|_, a| a != action);
+
+            for keys in keys {
+                keymap.bind(KeySequence::parse(keys)?, *action);
+            }
+        }
+
+        Ok(keymap)
+    }
+
+    pub fn bind(&mut self, keys: KeySequence, action: Action) {
+        self.bindings.insert(keys, action);
+    }
+
+    pub fn is_modal(&self) -> bool {
+        self.modal
+    }
+
+    /// Feeds a pressed key, completing a sequence or starting a new one
+    pub fn lookup(&mut self, key: &KeyEvent) -> Lookup {
+        self.pending.push(KeyBinding::from(key));
+
+        let sequence = KeySequence(self.pending.clone());
+        if let Some(action) = self.bindings.get(&sequence) {
+            self.pending.clear();
+            return Lookup::Action(*action);
+        }
+
+        let is_prefix = self
+            .bindings
+            .keys()
+            .any(|k| k.0.len() > self.pending.len() && k.0.starts_with(&self.pending));
+        if is_prefix {
+            return Lookup::Pending;
+        }
+
+        // A broken sequence is dropped, the last key may start a new one
+        let restart = self.pending.len() > 1;
+        self.pending.clear();
+        if restart {
+            return self.lookup(key);
+        }
+
+        Lookup::Unbound
+    }
+
+    /// All key sequences bound to `action`, in a stable order
+    pub fn keys(&self, action: Action) -> Vec<KeySequence> {
+        let mut keys: Vec<KeySequence> = self
+            .bindings
+            .iter()
+            .filter(|(_, a)| **a == action)
+            .map(|(k, _)| k.clone())
+            .collect();
+
+        keys.sort_by_key(|k| k.to_string());
+        keys
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(code: KeyCode, modifiers: KeyModifiers) -> KeyEvent {
+        KeyEvent::new(code, modifiers)
+    }
+
+    fn char_key(c: char) -> KeyEvent {
+        key(KeyCode::Char(c), KeyModifiers::NONE)
+    }
+
+    #[test]
+    fn to_latin_maps_the_russian_layout() {
+        assert_eq!(to_latin('й'), 'q');
+        assert_eq!(to_latin('Й'), 'Q');
+        assert_eq!(to_latin('ь'), 'm');
+        assert_eq!(to_latin('Ь'), 'M');
+    }
+
+    #[test]
+    fn to_latin_keeps_other_chars() {
+        for c in ['q', 'Q', '1', '?', '/', '.', ',', 'ж', 'Ж', 'ю', '№', 'ü'] {
+            assert_eq!(to_latin(c), c);
+        }
+    }
+
+    #[test]
+    fn parses_modifiers_and_named_keys() {
+        assert_eq!(
+            KeyBinding::parse("ctrl+r"),
+            Ok(KeyBinding::new(KeyCode::Char('r'), KeyModifiers::CONTROL))
+        );
+        assert_eq!(
+            KeyBinding::parse("Ctrl+Alt+PageDown"),
+            Ok(KeyBinding::new(
+                KeyCode::PageDown,
+                KeyModifiers::CONTROL | KeyModifiers::ALT
+            ))
+        );
+        assert_eq!(
+            KeyBinding::parse("f5"),
+            Ok(KeyBinding::new(KeyCode::F(5), KeyModifiers::NONE))
+        );
+        assert_eq!(
+            KeyBinding::parse("space"),
+            Ok(KeyBinding::new(KeyCode::Char(' '), KeyModifiers::NONE))
+        );
+    }
+
+    #[test]
+    fn parses_plus_as_a_key() {
+        let plus = KeyBinding::new(KeyCode::Char('+'), KeyModifiers::NONE);
+
+        assert_eq!(KeyBinding::parse("+"), Ok(plus));
+        assert_eq!(
+            KeyBinding::parse("ctrl++"),
+            Ok(KeyBinding::new(KeyCode::Char('+'), KeyModifiers::CONTROL))
+        );
+    }
+
+    #[test]
+    fn shift_is_part_of_the_char() {
+        assert_eq!(
+            KeyBinding::parse("shift+g"),
+            Ok(KeyBinding::new(KeyCode::Char('G'), KeyModifiers::NONE))
+        );
+        assert_eq!(KeyBinding::parse("shift+g"), KeyBinding::parse("G"));
+        assert_eq!(
+            KeyBinding::parse("shift+tab"),
+            Ok(KeyBinding::new(KeyCode::BackTab, KeyModifiers::NONE))
+        );
+        assert_eq!(
+            KeyBinding::from(&key(KeyCode::BackTab, KeyModifiers::SHIFT)),
+            KeyBinding::parse("shift+tab").unwrap()
+        );
+    }
+
+    #[test]
+    fn russian_keys_parse_to_latin() {
+        assert_eq!(KeyBinding::parse("alt+а"), KeyBinding::parse("alt+f"));
+        assert_eq!(KeyBinding::parse("ctrl+к"), KeyBinding::parse("ctrl+r"));
+    }
+
+    #[test]
+    fn rejects_unknown_keys() {
+        for s in ["", "hyper+r", "f13", "f0", "ctrl+", "pgleft", "ab"] {
+            assert_eq!(
+                KeyBinding::parse(s),
+                Err(KeymapError::UnknownKey(s.to_string())),
+                "{}",
+                s
+            );
+        }
+    }
+
+    #[test]
+    fn lookup_ignores_the_layout() {
+        let mut keymap = Keymap::default();
+
+        assert_eq!(
+            keymap.lookup(&key(KeyCode::Char('а'), KeyModifiers::ALT)),
+            Lookup::Action(Action::ToggleFavorite)
+        );
+        assert_eq!(
+            keymap.lookup(&key(KeyCode::Char('к'), KeyModifiers::CONTROL)),
+            Lookup::Action(Action::Refresh)
+        );
+        assert_eq!(keymap.lookup(&char_key('q')), Lookup::Unbound);
+    }
+
+    #[test]
+    fn punctuation_keeps_its_own_bindings() {
+        let mut keymap = Keymap::default();
+
+        assert_eq!(keymap.lookup(&char_key('?')), Lookup::Action(Action::Help));
+        assert_eq!(keymap.lookup(&char_key('/')), Lookup::Action(Action::Find));
+        assert_eq!(keymap.lookup(&char_key(',')), Lookup::Unbound);
+        assert_eq!(keymap.lookup(&char_key('.')), Lookup::Unbound);
+        // Starts a search in the default preset instead of the `:` prompt
+        assert_eq!(
+            keymap.lookup(&key(KeyCode::Char('Ж'), KeyModifiers::SHIFT)),
+            Lookup::Unbound
+        );
+    }
+
+    #[test]
+    fn overrides_replace_the_keys_of_an_action() {
+        let overrides = HashMap::from([(Action::Quit, vec![String::from("ctrl+q")])]);
+        let mut keymap = Keymap::with_overrides(Preset::Default, &overrides).unwrap();
+
+        assert_eq!(
+            keymap.lookup(&key(KeyCode::Esc, KeyModifiers::NONE)),
+            Lookup::Unbound
+        );
+        assert_eq!(
+            keymap.lookup(&key(KeyCode::Char('й'), KeyModifiers::CONTROL)),
+            Lookup::Action(Action::Quit)
+        );
+        assert_eq!(keymap.keys(Action::Quit).len(), 1);
+    }
+
+    #[test]
+    fn overrides_with_an_unknown_key_fail() {
+        let overrides = HashMap::from([(Action::Quit, vec![String::from("ctrl+nope")])]);
+
+        assert!(Keymap::with_overrides(Preset::Default, &overrides).is_err());
+    }
+
+    #[test]
+    fn parses_sequences() {
+        let sequence = KeySequence::parse("g  shift+t").unwrap();
+
+        assert_eq!(sequence.to_string(), "g T");
+        assert!(KeySequence::parse("").is_err());
+        assert!(KeySequence::parse("g nope+t").is_err());
+    }
+
+    #[test]
+    fn lookup_waits_for_the_rest_of_a_sequence() {
+        let mut keymap = Keymap::with_preset(Preset::Vim);
+
+        assert_eq!(keymap.lookup(&char_key('g')), Lookup::Pending);
+        assert_eq!(keymap.lookup(&char_key('g')), Lookup::Action(Action::First));
+        assert_eq!(keymap.lookup(&char_key('g')), Lookup::Pending);
+        assert_eq!(
+            keymap.lookup(&key(KeyCode::Char('T'), KeyModifiers::SHIFT)),
+            Lookup::Action(Action::PreviousTab)
+        );
+    }
+
+    #[test]
+    fn lookup_restarts_a_broken_sequence() {
+        let mut keymap = Keymap::with_preset(Preset::Vim);
+
+        assert_eq!(keymap.lookup(&char_key('g')), Lookup::Pending);
+        assert_eq!(keymap.lookup(&char_key('j')), Lookup::Action(Action::Down));
+        assert_eq!(keymap.lookup(&char_key('g')), Lookup::Pending);
+        assert_eq!(keymap.lookup(&char_key('x')), Lookup::Unbound);
+        assert_eq!(keymap.lookup(&char_key('j')), Lookup::Action(Action::Down));
+    }
+
+    #[test]
+    fn sequences_ignore_the_layout() {
+        let mut keymap = Keymap::with_preset(Preset::Vim);
+
+        assert_eq!(keymap.lookup(&char_key('п')), Lookup::Pending);
+        assert_eq!(keymap.lookup(&char_key('п')), Lookup::Action(Action::First));
+        assert_eq!(keymap.lookup(&char_key('о')), Lookup::Action(Action::Down));
+    }
+
+    #[test]
+    fn presets_differ_in_modal_keys() {
+        let mut default = Keymap::with_preset(Preset::Default);
+        let mut vim = Keymap::with_preset(Preset::Vim);
+
+        assert!(!default.is_modal());
+        assert!(vim.is_modal());
+        assert_eq!(default.lookup(&char_key('j')), Lookup::Unbound);
+        assert_eq!(vim.lookup(&char_key('j')), Lookup::Action(Action::Down));
+    }
+}
@@ -0,0 +1,125 @@
+use tui::layout::{Constraint, Direction, Layout, Rect};
+
+/// Narrower terminals show only the focused pane
+const MIN_SPLIT_WIDTH: u16 = 80;
+/// Lower terminals have no footer
+const MIN_FOOTER_HEIGHT: u16 = 12;
+/// Lower terminals don't show saved searches
+const MIN_SEARCHES_HEIGHT: u16 = 20;
+/// Borders and a single line of text
+const PROMPT_HEIGHT: u16 = 3;
+
+/// What decides where the panes go besides the size of the terminal
+pub struct LayoutState {
+    pub explorer_focused: bool,
+    /// Zen mode hides everything but the explorer while it is focused
+    pub zen: bool,
+    /// A text prompt is open, so the search box has to stay visible
+    pub prompt: bool,
+    pub saved_searches: usize,
+}
+
+/// Where every pane goes, hidden panes get an empty `Rect`
+#[derive(Debug, Default)]
+pub struct Areas {
+    pub search: Rect,
+    pub searches: Rect,
+    pub objects: Rect,
+    pub explorer: Rect,
+    pub footer: Rect,
+}
+
+/// Width of the list column, wider while the list is focused
+fn list_width(width: u16, explorer_focused: bool) -> u16 {
+    if explorer_focused {
+        (width / 4).clamp(28, 40)
+    } else {
+        (width * 2 / 5).clamp(36, 64)
+    }
+}
+
+pub fn areas(size: Rect, state: &LayoutState) -> Areas {
+    let mut areas = Areas::default();
+
+    let body = if size.height >= MIN_FOOTER_HEIGHT {
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Min(0), Constraint::Length(PROMPT_HEIGHT)])
+            .split(size);
+        areas.footer = chunks[1];
+        chunks[0]
+    } else {
+        size
+    };
+
+    let split = body.width >= MIN_SPLIT_WIDTH;
+
+    if state.explorer_focused && (state.zen || !split) {
+        areas.explorer = body;
+
+        // Prompts are typed in the search box, it goes on top of the explorer
+        if state.prompt {
+            let chunks = Layout::default()
+                .direction(Direction::Vertical)
+                .constraints([Constraint::Length(PROMPT_HEIGHT), Constraint::Min(0)])
+                .split(body);
+            areas.search = chunks[0];
+            areas.explorer = chunks[1];
+        }
+
+        return areas;
+    }
+
+    let left = if split {
+        let chunks = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([
+                Constraint::Length(list_width(body.width, state.explorer_focused)),
+                Constraint::Min(0),
+            ])
+            .split(body);
+        areas.explorer = chunks[1];
+        chunks[0]
+    } else {
+        body
+    };
+
+    let mut constraints = vec![Constraint::Length(PROMPT_HEIGHT)];
+    let show_searches = state.saved_searches > 0 && left.height >= MIN_SEARCHES_HEIGHT;
+    if show_searches {
+        // Up to five saved searches are visible at once, the rest are scrolled
+        constraints.push(Constraint::Length(state.saved_searches.min(5) as u16 + 2));
+    }
+    constraints.push(Constraint::Min(0));
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints(constraints)
+        .split(left);
+
+    areas.search = chunks[0];
+    if show_searches {
+        areas.searches = chunks[1];
+    }
+    areas.objects = chunks[chunks.len() - 1];
+
+    areas
+}
+
+/**
+Centers the article in `area` when it is wider than `max_width` columns of
+text plus the borders and the scrollbar. Zero means no limit.
+*/
+pub fn reading_column(area: Rect, max_width: u16) -> Rect {
+    let width = max_width.saturating_add(3);
+    if max_width == 0 || area.width <= width {
+        return area;
+    }
+
+    Rect::new(
+        area.x + (area.width - width) / 2,
+        area.y,
+        width,
+        area.height,
+    )
+}
@@ -0,0 +1,592 @@
+use std::{fmt::Display, sync::OnceLock};
+
+use serde::Deserialize;
+
+use crate::{filters::SortBy, keymap::Action, parsing::ClassificationScp};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Locale {
+    #[default]
+    Ru,
+    En,
+}
+
+static LOCALE: OnceLock<Locale> = OnceLock::new();
+
+impl Locale {
+    /**
+    Language of the first set variable of `LC_ALL`, `LC_MESSAGES` and `LANG`.
+    Russian for `ru_*`, english for any other language, `None` for the `C`
+    and `POSIX` locales.
+    */
+    pub fn from_env() -> Option<Locale> {
+        let value = ["LC_ALL", "LC_MESSAGES", "LANG"]
+            .iter()
+            .filter_map(|v| std::env::var(v).ok())
+            .find(|v| !v.is_empty())?;
+
+        match value.split(['_', '.', '@']).next() {
+            Some("ru") => Some(Locale::Ru),
+            Some("C" | "POSIX") | None => None,
+            Some(_) => Some(Locale::En),
+        }
+    }
+}
+
+/// Sets the locale of the whole app, only the first call has an effect
+pub fn init(locale: Locale) {
+    let _ = LOCALE.set(locale);
+}
+
+pub fn current() -> Locale {
+    LOCALE.get().copied().unwrap_or_default()
+}
+
+/// Catalog of the current locale
+pub fn messages() -> &'static Messages {
+    match current() {
+        Locale::Ru => &RU,
+        Locale::En => &EN,
+    }
+}
+
+/// Replaces every `{}` of the message with the next argument
+pub fn fill(message: &str, args: &[&dyn Display]) -> String {
+    let mut result = String::new();
+    let mut args = args.iter();
+
+    for (i, piece) in message.split("{}").enumerate() {
+        if i > 0 {
+            if let Some(arg) = args.next() {
+                result.push_str(&arg.to_string());
+            }
+        }
+        result.push_str(piece);
+    }
+
+    result
+}
+
+/// Every text of the interface, `{}` marks where [`fill`] puts the arguments
+pub struct Messages {
+    /* Titles */
+    pub objects: &'static str,
+    pub objects_loading: &'static str,
+    pub objects_list: &'static str,
+    pub saved_searches: &'static str,
+    pub explorer: &'static str,
+    pub search: &'static str,
+    pub save_search_as: &'static str,
+    pub find_in_article: &'static str,
+    pub find_in_list: &'static str,
+    pub jump_to_object: &'static str,
+    pub palette: &'static str,
+    pub help: &'static str,
+    pub article_position: &'static str,
+    pub match_position: &'static str,
+    pub offline: &'static str,
+
+    /* Status messages */
+    pub load_failed: &'static str,
+    pub object_not_found: &'static str,
+    pub no_matches_in_article: &'static str,
+    pub not_found: &'static str,
+    pub compare_needs_two_tabs: &'static str,
+    pub independent_scroll: &'static str,
+    pub sync_scroll: &'static str,
+    pub empty_command: &'static str,
+    pub unknown_command: &'static str,
+    pub error: &'static str,
+    pub save_failed: &'static str,
+    pub article_refreshed: &'static str,
+    pub favorite_added: &'static str,
+    pub favorite_removed: &'static str,
+    pub objects_stale: &'static str,
+    pub branch_switched: &'static str,
+    pub usage: &'static str,
+
+    /* The cache command */
+    pub cache_catalog: &'static str,
+    pub cache_no_catalog: &'static str,
+    pub cache_articles: &'static str,
+    pub cache_compression: &'static str,
+    pub cache_dictionary: &'static str,
+    pub cache_no_dictionary: &'static str,
+    pub cache_expired: &'static str,
+    pub cache_pruned: &'static str,
+    pub cache_cleared: &'static str,
+    pub kib: &'static str,
+    pub minutes: &'static str,
+    pub hours: &'static str,
+    pub days: &'static str,
+
+    /* The mirror, export and import commands */
+    pub mirror_no_objects: &'static str,
+    pub mirror_done: &'static str,
+    pub export_done: &'static str,
+    pub import_done: &'static str,
+
+    /* Errors */
+    pub cache_missing: &'static str,
+    pub cache_corrupted: &'static str,
+    pub cache_newer: &'static str,
+    pub cache_write_failed: &'static str,
+    pub store_empty: &'static str,
+    pub store_newer: &'static str,
+    pub store_compression: &'static str,
+    pub store_failed: &'static str,
+    pub archive_failed: &'static str,
+    pub not_an_archive: &'static str,
+    pub archive_newer: &'static str,
+    pub archive_corrupted: &'static str,
+    pub archive_too_large: &'static str,
+    pub config_read_failed: &'static str,
+    pub config_parse_failed: &'static str,
+    pub config_keymap: &'static str,
+    pub config_theme: &'static str,
+    pub missing_path: &'static str,
+    pub unknown_argument: &'static str,
+    pub unknown_key: &'static str,
+    pub unknown_color: &'static str,
+    pub unknown_theme_element: &'static str,
+
+    /* Hints of the status line */
+    pub hint_open: &'static str,
+    pub hint_find: &'static str,
+    pub hint_number: &'static str,
+    pub hint_sort: &'static str,
+    pub hint_commands: &'static str,
+    pub hint_apply: &'static str,
+    pub hint_delete: &'static str,
+    pub hint_wrap: &'static str,
+    pub hint_links: &'static str,
+    pub hint_follow: &'static str,
+    pub hint_tabs: &'static str,
+    pub hint_close: &'static str,
+    pub hint_to_list: &'static str,
+    pub hint_done: &'static str,
+    pub hint_confirm: &'static str,
+    pub hint_cancel: &'static str,
+    pub hint_choose: &'static str,
+    pub hint_run: &'static str,
+    pub hint_scroll: &'static str,
+    pub hint_help: &'static str,
+    pub hint_quit: &'static str,
+
+    /* Help */
+    pub all_actions: &'static str,
+    pub text_input: &'static str,
+    pub erase_char: &'static str,
+    pub any_char: &'static str,
+    pub start_search: &'static str,
+    pub mouse: &'static str,
+    pub click: &'static str,
+    pub click_action: &'static str,
+    pub click_selected: &'static str,
+    pub wheel: &'static str,
+    pub scrolling: &'static str,
+}
+
+static RU: Messages = Messages {
+    objects: "SCP Объекты",
+    objects_loading: "SCP Объекты (Загружаются)",
+    objects_list: "Список объектов",
+    saved_searches: "Сохранённые поиски",
+    explorer: "Обзор",
+    search: "Поиск",
+    save_search_as: "Сохранить «{}» как",
+    find_in_article: "Найти в статье",
+    find_in_list: "Найти в списке",
+    jump_to_object: "Перейти к объекту (номер или RU-номер)",
+    palette: "Палитра команд",
+    help: "Справка",
+    article_position: "{} · строка {} из {} / {}%",
+    match_position: " · совпадение {}/{}",
+    offline: "Нет сети",
+
+    load_failed: "Не удалось загрузить {}",
+    object_not_found: "Объект SCP-{} не найден",
+    no_matches_in_article: "Нет совпадений в статье",
+    not_found: "Не найдено: {}",
+    compare_needs_two_tabs: "Для сравнения откройте две вкладки",
+    independent_scroll: "Прокрутка при сравнении: раздельная",
+    sync_scroll: "Прокрутка при сравнении: синхронная",
+    empty_command: "Пустая команда",
+    unknown_command: "Неизвестная команда: {}",
+    error: "Ошибка",
+    save_failed: "Не удалось сохранить: {}",
+    article_refreshed: "Статья загружена заново",
+    favorite_added: "{} добавлен в избранное",
+    favorite_removed: "{} убран из избранного",
+    objects_stale: "Не все серии загрузились, список объектов не обновлён",
+    branch_switched: "Ветка: {}",
+    usage: "Использование: scpfoundation-explorer [ПАРАМЕТРЫ] [КОМАНДА]
+
+Команды:
+  debug               Режим отладки
+  cache stats         Размер и возраст кэша
+  cache list          Все сохранённые статьи
+  cache prune         Удалить устаревшие статьи
+  cache clear         Удалить объекты и статьи
+  mirror              Загрузить все статьи, чтобы читать без сети
+  export <ФАЙЛ>       Выгрузить объекты и статьи в архив, с --favorites и избранное
+  import <ФАЙЛ>       Добавить архив к сохранённым объектам и статьям
+
+Параметры:
+  --config <ФАЙЛ>     Конфиг вместо $XDG_CONFIG_HOME/scpfoundation-explorer/config.toml
+  --data-dir <ПАПКА>  Хранилище, поиски и вкладки вместо $XDG_DATA_HOME/scpfoundation-explorer
+  --cache-dir <ПАПКА> Кэш прошлых версий для импорта вместо $XDG_CACHE_HOME/scpfoundation-explorer
+  -h, --help          Показать эту справку",
+
+    cache_catalog: "Объекты: {}, загружены {} назад",
+    cache_no_catalog: "Объекты: не сохранены",
+    cache_articles: "Статьи: {}, устарели: {}",
+    cache_compression: "Размер статей: {}, на диске {} ({}%)",
+    cache_dictionary: "Словарь сжатия: {}",
+    cache_no_dictionary: "Словарь сжатия: ещё не обучен",
+    cache_expired: "устарела",
+    cache_pruned: "Удалено статей: {}",
+    cache_cleared: "Объекты и статьи удалены, избранное и история сохранены",
+    kib: "{} КиБ",
+    minutes: "{} мин",
+    hours: "{} ч",
+    days: "{} дн",
+
+    mirror_no_objects: "Не удалось загрузить список объектов",
+    mirror_done: "Загружено: {}, уже были: {}, с ошибкой: {}",
+    export_done: "Объектов: {}, статей: {}, избранных: {} выгружено в {}",
+    import_done: "Из архива взято объектов: {}, статей: {}, избранных: {}",
+
+    cache_missing: "Кэш ещё не создан",
+    cache_corrupted: "Кэш повреждён",
+    cache_newer: "Формат кэша {} новее поддерживаемого {}",
+    cache_write_failed: "Не удалось записать кэш: {}",
+    store_empty: "Ещё ничего не загружено",
+    store_newer: "Схема хранилища {} новее поддерживаемой {}",
+    store_compression: "Не удалось сжать статью: {}",
+    store_failed: "Не удалось открыть хранилище: {}",
+    archive_failed: "Не удалось открыть архив: {}",
+    not_an_archive: "Это не архив программы",
+    archive_newer: "Формат архива {} новее поддерживаемого {}",
+    archive_corrupted: "Архив повреждён",
+    archive_too_large: "Данные занимают {} МиБ, в архив помещается не больше {} МиБ",
+    config_read_failed: "Не удалось прочитать {}: {}",
+    config_parse_failed: "Не удалось разобрать {}: {}",
+    config_keymap: "Неверные клавиши в {}: {}",
+    config_theme: "Неверная тема в {}: {}",
+    missing_path: "После `{}` нужен путь",
+    unknown_argument: "Неизвестный аргумент `{}`, см. --help",
+    unknown_key: "Неизвестная клавиша `{}`",
+    unknown_color: "Неизвестный цвет `{}`",
+    unknown_theme_element: "Неизвестный элемент темы `{}`",
+
+    hint_open: "Открыть",
+    hint_find: "Найти",
+    hint_number: "Номер",
+    hint_sort: "Сортировка",
+    hint_commands: "Команды",
+    hint_apply: "Применить",
+    hint_delete: "Удалить",
+    hint_wrap: "Перенос",
+    hint_links: "Ссылки",
+    hint_follow: "Перейти",
+    hint_tabs: "Вкладки",
+    hint_close: "Закрыть",
+    hint_to_list: "К списку",
+    hint_done: "Готово",
+    hint_confirm: "Подтвердить",
+    hint_cancel: "Отменить",
+    hint_choose: "Выбрать",
+    hint_run: "Выполнить",
+    hint_scroll: "Листать",
+    hint_help: "Справка",
+    hint_quit: "Выйти",
+
+    all_actions: "Все действия",
+    text_input: "Ввод текста",
+    erase_char: "Стереть символ",
+    any_char: "Любой символ",
+    start_search: "Начать поиск",
+    mouse: "Мышь",
+    click: "Клик",
+    click_action: "Выбрать окно и строку, перейти по ссылке",
+    click_selected: "Клик по выбранному",
+    wheel: "Колесо",
+    scrolling: "Прокрутка",
+};
+
+static EN: Messages = Messages {
+    objects: "SCP Objects",
+    objects_loading: "SCP Objects (loading)",
+    objects_list: "Objects list",
+    saved_searches: "Saved searches",
+    explorer: "Explorer",
+    search: "Search",
+    save_search_as: "Save “{}” as",
+    find_in_article: "Find in the article",
+    find_in_list: "Find in the list",
+    jump_to_object: "Go to object (number or RU-number)",
+    palette: "Command palette",
+    help: "Help",
+    article_position: "{} · line {} of {} / {}%",
+    match_position: " · match {}/{}",
+    offline: "Offline",
+
+    load_failed: "Can't load {}",
+    object_not_found: "Object SCP-{} not found",
+    no_matches_in_article: "No matches in the article",
+    not_found: "Not found: {}",
+    compare_needs_two_tabs: "Open two tabs to compare them",
+    independent_scroll: "Scrolling while comparing: independent",
+    sync_scroll: "Scrolling while comparing: synchronized",
+    empty_command: "Empty command",
+    unknown_command: "Unknown command: {}",
+    error: "Error",
+    save_failed: "Can't save: {}",
+    article_refreshed: "The article is loaded again",
+    favorite_added: "{} is added to the favorites",
+    favorite_removed: "{} is removed from the favorites",
+    objects_stale: "Some series didn't load, the objects aren't refreshed",
+    branch_switched: "Branch: {}",
+    usage: "Usage: scpfoundation-explorer [OPTIONS] [COMMAND]
+
+Commands:
+  debug               Debug mode
+  cache stats         Size and age of the cache
+  cache list          Every cached article
+  cache prune         Remove the expired articles
+  cache clear         Remove the objects and articles
+  mirror              Load every article to read without network
+  export <FILE>       Write the objects and articles to an archive, --favorites adds the favorites
+  import <FILE>       Merge an archive into the cached objects and articles
+
+Options:
+  --config <FILE>     Config file instead of $XDG_CONFIG_HOME/scpfoundation-explorer/config.toml
+  --data-dir <DIR>    Store, saved searches and tabs instead of $XDG_DATA_HOME/scpfoundation-explorer
+  --cache-dir <DIR>   Cache of older versions to import instead of $XDG_CACHE_HOME/scpfoundation-explorer
+  -h, --help          Print this help",
+
+    cache_catalog: "Objects: {}, scraped {} ago",
+    cache_no_catalog: "Objects: not cached",
+    cache_articles: "Articles: {}, expired: {}",
+    cache_compression: "Size of the articles: {}, stored in {} ({}%)",
+    cache_dictionary: "Compression dictionary: {}",
+    cache_no_dictionary: "Compression dictionary: not trained yet",
+    cache_expired: "expired",
+    cache_pruned: "Removed articles: {}",
+    cache_cleared: "Objects and articles are removed, favorites and history are kept",
+    kib: "{} KiB",
+    minutes: "{} min",
+    hours: "{} h",
+    days: "{} d",
+
+    mirror_no_objects: "Can't load the objects",
+    mirror_done: "Loaded: {}, already cached: {}, failed: {}",
+    export_done: "Exported {} objects, {} articles and {} favorites to {}",
+    import_done: "Taken from the archive: {} objects, {} articles, {} favorites",
+
+    cache_missing: "The cache doesn't exist yet",
+    cache_corrupted: "The cache is corrupted",
+    cache_newer: "Cache format {} is newer than {}",
+    cache_write_failed: "Can't write the cache: {}",
+    store_empty: "Nothing is cached yet",
+    store_newer: "Store schema {} is newer than {}",
+    store_compression: "Can't compress an article: {}",
+    store_failed: "Can't use the store: {}",
+    archive_failed: "Can't use the archive: {}",
+    not_an_archive: "Not an archive of the explorer",
+    archive_newer: "Archive format {} is newer than {}",
+    archive_corrupted: "The archive is corrupted",
+    archive_too_large: "The data takes {} MiB, an archive holds at most {} MiB",
+    config_read_failed: "Can't read {}: {}",
+    config_parse_failed: "Can't parse {}: {}",
+    config_keymap: "Invalid keymap in {}: {}",
+    config_theme: "Invalid theme in {}: {}",
+    missing_path: "`{}` needs a path",
+    unknown_argument: "Unknown argument `{}`, see --help",
+    unknown_key: "Unknown key `{}`",
+    unknown_color: "Unknown color `{}`",
+    unknown_theme_element: "Unknown theme element `{}`",
+
+    hint_open: "Open",
+    hint_find: "Find",
+    hint_number: "Number",
+    hint_sort: "Sort",
+    hint_commands: "Commands",
+    hint_apply: "Apply",
+    hint_delete: "Delete",
+    hint_wrap: "Wrap",
+    hint_links: "Links",
+    hint_follow: "Follow",
+    hint_tabs: "Tabs",
+    hint_close: "Close",
+    hint_to_list: "To the list",
+    hint_done: "Done",
+    hint_confirm: "Confirm",
+    hint_cancel: "Cancel",
+    hint_choose: "Choose",
+    hint_run: "Run",
+    hint_scroll: "Scroll",
+    hint_help: "Help",
+    hint_quit: "Quit",
+
+    all_actions: "All actions",
+    text_input: "Text input",
+    erase_char: "Erase a character",
+    any_char: "Any character",
+    start_search: "Start a search",
+    mouse: "Mouse",
+    click: "Click",
+    click_action: "Focus the pane and the row, follow a link",
+    click_selected: "Click on the selected row",
+    wheel: "Wheel",
+    scrolling: "Scroll",
+};
+
+pub fn class_name(class: &ClassificationScp) -> &'static str {
+    match current() {
+        Locale::Ru => match class {
+            ClassificationScp::Euclid => "Евклид",
+            ClassificationScp::Keter => "Кетер",
+            ClassificationScp::Neutralized => "Нейтрализован",
+            ClassificationScp::None => "Отсутствует",
+            ClassificationScp::Safe => "Безопасный",
+            ClassificationScp::Thaumiel => "Таумиэль",
+            ClassificationScp::NonStandard => "Нестандартный класс",
+        },
+        Locale::En => match class {
+            ClassificationScp::Euclid => "Euclid",
+            ClassificationScp::Keter => "Keter",
+            ClassificationScp::Neutralized => "Neutralized",
+            ClassificationScp::None => "None",
+            ClassificationScp::Safe => "Safe",
+            ClassificationScp::Thaumiel => "Thaumiel",
+            ClassificationScp::NonStandard => "Non-standard",
+        },
+    }
+}
+
+pub fn sort_name(sort: SortBy) -> &'static str {
+    match current() {
+        Locale::Ru => match sort {
+            SortBy::Scrape => "по порядку",
+            SortBy::Number => "по номеру",
+            SortBy::Title => "по названию",
+            SortBy::Class => "по классу",
+            SortBy::Rating => "по рейтингу",
+        },
+        Locale::En => match sort {
+            SortBy::Scrape => "as scraped",
+            SortBy::Number => "by number",
+            SortBy::Title => "by title",
+            SortBy::Class => "by class",
+            SortBy::Rating => "by rating",
+        },
+    }
+}
+
+pub fn action_description(action: Action) -> &'static str {
+    match current() {
+        Locale::Ru => match action {
+            Action::Quit => "Выйти",
+            Action::NextWindow => "Следующее окно",
+            Action::PreviousWindow => "Предыдущее окно",
+            Action::Up => "Вверх",
+            Action::Down => "Вниз",
+            Action::PageUp => "Страница вверх",
+            Action::PageDown => "Страница вниз",
+            Action::HalfPageUp => "Полстраницы вверх",
+            Action::HalfPageDown => "Полстраницы вниз",
+            Action::First => "В начало",
+            Action::Last => "В конец",
+            Action::Open => "Открыть",
+            Action::Refresh => "Загрузить список или статью с сайта заново",
+            Action::SaveSearch => "Сохранить поиск",
+            Action::DeleteSearch => "Удалить сохранённый поиск",
+            Action::FilterSafe => "Фильтр: Безопасный",
+            Action::FilterEuclid => "Фильтр: Евклид",
+            Action::FilterKeter => "Фильтр: Кетер",
+            Action::FilterThaumiel => "Фильтр: Таумиэль",
+            Action::FilterNeutralized => "Фильтр: Нейтрализован",
+            Action::FilterNonStandard => "Фильтр: Нестандартный класс",
+            Action::FilterNone => "Фильтр: Класс отсутствует",
+            Action::ClearFilters => "Сбросить фильтры и сортировку",
+            Action::CycleSort => "Сменить сортировку",
+            Action::ReverseSort => "Обратить сортировку",
+            Action::Command => "Перейти к объекту по номеру",
+            Action::Palette => "Палитра команд",
+            Action::Find => "Найти",
+            Action::Search => "Фильтровать список по запросу",
+            Action::NextMatch => "Следующее совпадение",
+            Action::PreviousMatch => "Предыдущее совпадение",
+            Action::Close => "Закрыть",
+            Action::NextLink => "Следующая ссылка в статье",
+            Action::PreviousLink => "Предыдущая ссылка в статье",
+            Action::ScrollLeft => "Прокрутить статью влево",
+            Action::ScrollRight => "Прокрутить статью вправо",
+            Action::ToggleWrap => "Перенос строк в статье",
+            Action::ToggleZen => "Режим чтения без списка",
+            Action::OpenInNewTab => "Открыть в новой вкладке",
+            Action::NextTab => "Следующая вкладка",
+            Action::PreviousTab => "Предыдущая вкладка",
+            Action::Compare => "Сравнить две вкладки",
+            Action::ToggleFavorite => "Добавить в избранное или убрать",
+            Action::ToggleTheme => "Сменить тему",
+            Action::SwitchBranch => "Сменить ветку сайта",
+            Action::Export => "Выгрузить хранилище в архив",
+            Action::ToggleSyncScroll => "Синхронная прокрутка при сравнении",
+            Action::Help => "Справка по клавишам",
+        },
+        Locale::En => match action {
+            Action::Quit => "Quit",
+            Action::NextWindow => "Next pane",
+            Action::PreviousWindow => "Previous pane",
+            Action::Up => "Up",
+            Action::Down => "Down",
+            Action::PageUp => "Page up",
+            Action::PageDown => "Page down",
+            Action::HalfPageUp => "Half a page up",
+            Action::HalfPageDown => "Half a page down",
+            Action::First => "Go to the top",
+            Action::Last => "Go to the bottom",
+            Action::Open => "Open",
+            Action::Refresh => "Load the list or the article from the site again",
+            Action::SaveSearch => "Save the search",
+            Action::DeleteSearch => "Delete the saved search",
+            Action::FilterSafe => "Filter: Safe",
+            Action::FilterEuclid => "Filter: Euclid",
+            Action::FilterKeter => "Filter: Keter",
+            Action::FilterThaumiel => "Filter: Thaumiel",
+            Action::FilterNeutralized => "Filter: Neutralized",
+            Action::FilterNonStandard => "Filter: Non-standard",
+            Action::FilterNone => "Filter: No class",
+            Action::ClearFilters => "Clear filters and sorting",
+            Action::CycleSort => "Change sorting",
+            Action::ReverseSort => "Reverse sorting",
+            Action::Command => "Go to object by number",
+            Action::Palette => "Command palette",
+            Action::Find => "Find",
+            Action::Search => "Filter the list by a query",
+            Action::NextMatch => "Next match",
+            Action::PreviousMatch => "Previous match",
+            Action::Close => "Close",
+            Action::NextLink => "Next link in the article",
+            Action::PreviousLink => "Previous link in the article",
+            Action::ScrollLeft => "Scroll the article left",
+            Action::ScrollRight => "Scroll the article right",
+            Action::ToggleWrap => "Wrap lines of the article",
+            Action::ToggleZen => "Reading mode without the list",
+            Action::OpenInNewTab => "Open in a new tab",
+            Action::NextTab => "Next tab",
+            Action::PreviousTab => "Previous tab",
+            Action::Compare => "Compare two tabs",
+            Action::ToggleFavorite => "Add to the favorites or remove",
+            Action::ToggleTheme => "Switch the theme",
+            Action::SwitchBranch => "Switch the branch of the site",
+            Action::Export => "Export the store into an archive",
+            Action::ToggleSyncScroll => "Synchronized scrolling while comparing",
+            Action::Help => "Key bindings",
+        },
+    }
+}
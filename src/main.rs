@@ -1,41 +1,103 @@
+pub mod archive;
+pub mod article;
 pub mod caching;
+pub mod cli;
+pub mod commands;
+pub mod compression;
+pub mod config;
+pub mod filters;
+pub mod help;
+pub mod keymap;
+pub mod layout;
+pub mod locale;
+pub mod palette;
 pub mod parsing;
+pub mod paths;
+pub mod searches;
 pub mod stateful;
-
+pub mod store;
+pub mod tabs;
+pub mod theme;
+
+use article::{Article, Mark};
+use caching::{cache_searches, cache_tabs, decache_searches, decache_tabs};
+use cli::{CacheCommand, Cli, Subcommand};
+use commands::Command;
+use config::Config;
 use crossterm::{
-    event::{self, DisableMouseCapture, Event, KeyCode},
+    event::{
+        self, DisableMouseCapture, EnableMouseCapture, Event, KeyCode, KeyModifiers, MouseButton,
+        MouseEvent, MouseEventKind,
+    },
     execute,
     terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
 };
-use parsing::{parse_all, parse_object_page, ScpObject};
+use filters::Filters;
+use help::Overlay;
+use keymap::{Action, Keymap, Lookup};
+use layout::LayoutState;
+use locale::{fill, messages, Locale};
+use palette::Palette;
+use parsing::{
+    fetch_page, mirror, parse_all, parse_object_page, parse_page, refresh_all, scrape_all,
+    ApiObjectResult, Catalog, ClassificationScp, ScpObject, Site, BRANCHES,
+};
+use paths::Paths;
+use searches::{Query, SavedSearch};
 use stateful::StatefulList;
 use std::{
+    collections::{HashMap, HashSet},
     env,
     error::Error,
     io,
+    path::Path,
+    process::ExitCode,
     sync::Arc,
     time::{Duration, Instant},
 };
-use tokio::sync::{mpsc::channel, Mutex};
+use store::StoreError;
+use tabs::{SavedTabs, Tabs};
+use theme::{Theme, ThemeName};
+use tokio::sync::mpsc::{channel, Receiver, Sender};
 use tui::{
     backend::{Backend, CrosstermBackend},
-    layout::{Alignment, Constraint, Direction, Layout},
-    style::{Color, Modifier, Style},
+    layout::{Alignment, Constraint, Direction, Layout, Rect},
+    style::Style,
     text::{Span, Spans},
-    widgets::{Block, Borders, List, ListItem, Paragraph, Wrap},
+    widgets::{Block, Borders, Clear, List, ListItem, Paragraph},
     Frame, Terminal,
 };
 
+/// Archive written by the `Export` action, in the directory the app was started from
+const EXPORT_FILE: &str = "scpfoundation-explorer.scpa";
+
 #[derive(PartialEq, Clone)]
 enum WindowSelect {
     Explorer,
     Objects,
+    Searches,
 }
 
 #[derive(PartialEq, Eq, Clone)]
 enum Mode {
     Default,
     Search,
+    SaveSearch,
+    Command,
+    Palette,
+    Find,
+    Help,
+}
+
+/// Where the panes were drawn last time, to find what is under the mouse
+#[derive(Clone, Default)]
+struct Panes {
+    objects: Rect,
+    searches: Rect,
+    explorer: Rect,
+    /// The compared article which is not active
+    compare: Rect,
+    tab_bar: Rect,
 }
 
 #[derive(Clone)]
@@ -44,66 +106,168 @@ struct AppStates {
     search: String,
     mode: Mode,
     is_load: bool,
-    objects: Option<Vec<ScpObject>>,
+    /// The whole catalog, only objects passing the search and filters are in the view
     objects_items: StatefulList<ScpObject>,
-    explorer: Option<String>,
-    scroll: (u16, u16),
+    filters: Filters,
+    /// Ratings of the visited objects by their lowercase id, e.g. `ru-1234`
+    ratings: HashMap<String, i32>,
+    /// Page ids of the favorite objects and articles
+    favorites: HashSet<String>,
+    /// Page ids opened at least once, marked in the objects list
+    visited: HashSet<String>,
+    saved_searches: StatefulList<SavedSearch>,
+    saved_counts: Vec<usize>,
+    input: String,
+    /// Query of the last `Find`, repeated by `NextMatch` and `PreviousMatch`
+    find: String,
+    palette: Palette,
+    help: Overlay,
+    explorer: Tabs,
+    panes: Panes,
+    keymap: Keymap,
+    theme: Theme,
+    theme_name: ThemeName,
+    /// Colors of the config, kept over every theme switched to
+    colors: HashMap<String, String>,
+    /// Zen mode hides the lists while the explorer is focused
+    zen: bool,
+    /// Longest line of an article in columns, zero means no limit
+    reading_width: u16,
+    site: Site,
+    /// Branch of the config, the objects of the other branches aren't cached
+    cached_branch: String,
+    /// How long the cached objects are used before scraping them again
+    cache_ttl: Duration,
+    article_ttl: Duration,
+    /// Result of the last command, shown in the bottom block
+    status: Option<String>,
+    quit: bool,
 }
 
-#[derive(Clone)]
-struct ObjectsLoading {
-    objects: Option<Vec<ScpObject>>,
-    objects_items: StatefulList<ScpObject>,
+#[tokio::main]
+async fn main() -> ExitCode {
+    match run().await {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            // The arguments or the config may fail before the locale is known
+            locale::init(Locale::from_env().unwrap_or_default());
+            eprintln!("{}: {}", messages().error, err);
+            ExitCode::FAILURE
+        }
+    }
 }
 
-#[tokio::main]
-async fn main() -> Result<(), Box<dyn Error>> {
+/// Runs a subcommand or the interface, the errors are printed by [`main`]
+async fn run() -> Result<(), Box<dyn Error>> {
+    let cli = Cli::parse(env::args().skip(1))?;
+    paths::init(Paths::resolve(&cli));
+
+    let config = Config::load(paths::config_file())?;
+    locale::init(config.locale.or_else(Locale::from_env).unwrap_or_default());
+
+    let mut saved_searches = StatefulList::with_items(decache_searches().unwrap_or_default());
+    saved_searches.set_wrap(config.wrap_around);
+    let mut objects_items = StatefulList::new();
+    objects_items.set_wrap(config.wrap_around);
+
     let mut app: AppStates = AppStates {
         window: WindowSelect::Objects,
         search: String::new(),
         mode: Mode::Default,
         is_load: true,
-        objects: None,
-        objects_items: StatefulList::new(),
-        explorer: None,
-        scroll: (0, 0),
+        objects_items,
+        filters: Filters::default(),
+        ratings: HashMap::new(),
+        favorites: HashSet::new(),
+        visited: HashSet::new(),
+        saved_searches,
+        saved_counts: Vec::new(),
+        input: String::new(),
+        find: String::new(),
+        palette: Palette::default(),
+        help: Overlay::default(),
+        explorer: decache_tabs().map(Tabs::from).unwrap_or_default(),
+        panes: Panes::default(),
+        keymap: config.keymap()?,
+        theme: config.theme()?,
+        theme_name: config.theme,
+        colors: config.colors.clone(),
+        zen: false,
+        reading_width: config.reading_width,
+        site: config.site(),
+        cached_branch: config.branch.clone(),
+        cache_ttl: config.cache_ttl(),
+        article_ttl: config.article_ttl(),
+        status: None,
+        quit: false,
     };
 
-    let objects_loader = Arc::new(Mutex::new(ObjectsLoading {
-        objects: None,
-        objects_items: StatefulList::new(),
-    }));
-    let objects_loader2 = Arc::clone(&objects_loader);
-
-    // Collect all arguments
-    let args: Vec<String> = env::args().collect();
-
-    // If arguments have string debug
-    if args.contains(&String::from("debug")) {
-        parsing::debug().await;
-        return Ok(());
+    match cli.subcommand {
+        Some(Subcommand::Debug) => {
+            parsing::debug().await;
+            return Ok(());
+        }
+        Some(Subcommand::Help) => {
+            println!("{}", messages().usage);
+            return Ok(());
+        }
+        Some(Subcommand::Cache(command)) => {
+            run_cache_command(command, app.cache_ttl, app.article_ttl)?;
+            return Ok(());
+        }
+        Some(Subcommand::Mirror) => {
+            run_mirror(&app).await;
+            return Ok(());
+        }
+        Some(Subcommand::Export { path, favorites }) => {
+            let snapshot = archive::export(&path, favorites)?;
+            println!(
+                "{}",
+                fill(
+                    messages().export_done,
+                    &[
+                        &snapshot.objects.len(),
+                        &snapshot.articles.len(),
+                        &snapshot.favorites.len(),
+                        &path.display()
+                    ]
+                )
+            );
+            return Ok(());
+        }
+        Some(Subcommand::Import(path)) => {
+            let report = archive::import(&path)?;
+            println!(
+                "{}",
+                fill(
+                    messages().import_done,
+                    &[&report.objects, &report.articles, &report.favorites]
+                )
+            );
+            return Ok(());
+        }
+        None => {}
     }
 
-    tokio::spawn(async move {
-        let mut lock = objects_loader.lock().await;
-
-        if lock.objects.is_none() {
-            lock.objects = Some(parse_all().await);
-        }
+    // Only the interface needs these, the subcommands above don't open the store for them
+    app.ratings = store::ratings().unwrap_or_default();
+    app.favorites = store::favorites().unwrap_or_default();
+    app.visited = store::visited().unwrap_or_default();
 
-        lock.objects_items = StatefulList::with_items(lock.objects.clone().unwrap());
-    });
+    let (tx, rx) = channel(1);
+    load_objects(&app, tx.clone(), false);
 
     // setup terminal
     enable_raw_mode()?;
     let mut stdout = io::stdout();
-    execute!(stdout, EnterAlternateScreen)?;
+    execute!(stdout, EnterAlternateScreen, EnableMouseCapture)?;
     let backend = CrosstermBackend::new(stdout);
     let mut terminal = Terminal::new(backend)?;
 
     // create app and run it
     let tick_rate = Duration::from_millis(50);
-    let res = run_app(&mut terminal, &mut app, tick_rate, objects_loader2).await;
+    let res = run_app(&mut terminal, &mut app, tick_rate, tx, rx).await;
+    let saved = cache_tabs(&SavedTabs::from(&app.explorer));
 
     // restore terminal
     disable_raw_mode()?;
@@ -115,11 +279,51 @@ async fn main() -> Result<(), Box<dyn Error>> {
     )?;
     terminal.show_cursor()?;
 
-    if let Err(err) = res {
-        println!("{:?}", err)
+    if let Err(err) = saved {
+        eprintln!("{}", fill(messages().save_failed, &[&err]))
     }
 
-    Ok(())
+    Ok(res?)
+}
+
+/**
+Loads the objects in background, `force` skips the cache and scrapes the site
+*/
+fn load_objects(app: &AppStates, tx: Sender<Catalog>, force: bool) {
+    let site = app.site.clone();
+    let ttl = app.cache_ttl;
+    let cached = site.branch == app.cached_branch;
+
+    tokio::spawn(async move {
+        let catalog = if !cached {
+            match scrape_all(&site).await {
+                Some(objects) => Catalog {
+                    objects,
+                    stale: false,
+                },
+                None => Catalog {
+                    objects: Vec::new(),
+                    stale: true,
+                },
+            }
+        } else if force {
+            match refresh_all(&site).await {
+                Some(objects) => Catalog {
+                    objects,
+                    stale: false,
+                },
+                None => Catalog {
+                    objects: Vec::new(),
+                    stale: true,
+                },
+            }
+        } else {
+            parse_all(&site, ttl).await
+        };
+
+        // The receiver is gone only when the app is closing
+        let _ = tx.send(catalog).await;
+    });
 }
 
 fn search(app: &mut AppStates) {
@@ -127,365 +331,1710 @@ fn search(app: &mut AppStates) {
         return;
     }
 
-    let objects: Vec<ScpObject>;
-    if app.search.len() > 0 {
-        objects = app
-            .objects
-            .clone()
-            .unwrap()
-            .iter()
-            .map(|x| x.clone())
-            .filter(|o| {
-                o.get_document_name()
-                    .to_lowercase()
-                    .contains(&app.search.to_lowercase())
-                    || o.get_name()
-                        .to_lowercase()
-                        .contains(&app.search.to_lowercase())
-            })
-            .collect::<Vec<ScpObject>>();
+    let query = Query::parse(&app.search);
+    let objects = app.objects_items.all();
+
+    let mut view: Vec<usize> = objects
+        .iter()
+        .enumerate()
+        .filter(|(_, o)| app.filters.matches(o) && query.matches(o))
+        .map(|(i, _)| i)
+        .collect();
+
+    app.filters.sort(&mut view, objects, &app.ratings);
+
+    app.objects_items.set_view(view);
+}
+
+fn toggle_class_filter(app: &mut AppStates, class: ClassificationScp) {
+    app.filters.toggle_class(class);
+    search(app);
+}
+
+/// Evaluates every saved search against the current catalog
+fn count_saved_searches(app: &mut AppStates) {
+    let objects = app.objects_items.all();
+    app.saved_counts = app
+        .saved_searches
+        .iter()
+        .map(|s| s.count(objects))
+        .collect();
+}
+
+fn switch_window(app: &mut AppStates, forward: bool) {
+    let mut windows = vec![WindowSelect::Objects, WindowSelect::Explorer];
+    if !app.saved_searches.is_empty() {
+        windows.insert(0, WindowSelect::Searches);
+    }
+
+    let i = windows.iter().position(|w| *w == app.window).unwrap_or(0);
+    let i = if forward {
+        (i + 1) % windows.len()
     } else {
-        objects = app.objects.clone().unwrap();
+        (i + windows.len() - 1) % windows.len()
+    };
+
+    app.window = windows[i].clone();
+    if app.window == WindowSelect::Searches && app.saved_searches.state.selected().is_none() {
+        app.saved_searches.select_first();
+    }
+}
+
+fn save_search(app: &mut AppStates) {
+    let name = app.input.trim().to_string();
+    if name.is_empty() || app.search.trim().is_empty() {
+        return;
     }
 
-    app.objects_items = StatefulList::with_items(objects);
+    app.saved_searches
+        .push(SavedSearch::new(name, app.search.trim().to_string()));
+    save_searches(app);
+    count_saved_searches(app);
 }
 
-async fn run_app<B: Backend>(
-    terminal: &mut Terminal<B>,
+fn save_searches(app: &mut AppStates) {
+    if let Err(e) = cache_searches(app.saved_searches.all()) {
+        app.status = Some(fill(messages().save_failed, &[&e]));
+    }
+}
+
+/**
+Shows the page in the active tab or in a new one, the opened article stays if
+the page failed to load
+*/
+fn show_article(
     app: &mut AppStates,
-    tick_rate: Duration,
-    objects: Arc<Mutex<ObjectsLoading>>,
-) -> io::Result<()> {
-    let mut last_tick = Instant::now();
-    let (tx, mut rx) = channel(100);
+    page: Option<ApiObjectResult>,
+    name: String,
+    new_tab: bool,
+) -> bool {
+    if let Some(r) = &page {
+        // Only the marks in the list are lost without the history
+        let _ = store::record_visit(&r.page_id);
+        app.visited.insert(r.page_id.clone());
+    }
 
-    tokio::spawn(async move {
-        let lock = objects.lock().await;
-        loop {
-            if lock.objects.is_some() {
-                match tx.send(lock.clone()).await {
-                    Ok(_) => {}
-                    Err(_) => panic!("Fuck!!"),
-                }
-                break;
-            }
+    match page {
+        Some(r) if new_tab => {
+            app.explorer.open_new(Article::from(r));
+            app.window = WindowSelect::Explorer;
+            true
         }
-    });
+        Some(r) => {
+            app.explorer.open(Article::from(r));
+            app.window = WindowSelect::Explorer;
+            true
+        }
+        None => {
+            app.status = Some(fill(messages().load_failed, &[&name]));
+            false
+        }
+    }
+}
 
-    loop {
-        terminal.draw(|f| ui(f, app))?;
+async fn open_article(app: &mut AppStates, id: String, new_tab: bool) -> bool {
+    let page = parse_object_page(&app.site, id.as_str(), app.article_ttl).await;
+
+    if let Some(rating) = page.as_ref().and_then(|r| r.rating) {
+        app.ratings.insert(id.to_lowercase(), rating);
+    }
+
+    show_article(app, page, format!("SCP-{}", id.to_uppercase()), new_tab)
+}
+
+/// Whether a cache entry of this age should be scraped again, `ttl` of zero never expires
+fn is_expired(age: Duration, ttl: Duration) -> bool {
+    !ttl.is_zero() && age > ttl
+}
+
+fn format_size(bytes: u64) -> String {
+    fill(messages().kib, &[&bytes.div_ceil(1024)])
+}
+
+fn format_age(age: Duration) -> String {
+    let minutes = age.as_secs() / 60;
+    match minutes {
+        m if m < 60 => fill(messages().minutes, &[&m]),
+        m if m < 60 * 24 => fill(messages().hours, &[&(m / 60)]),
+        m => fill(messages().days, &[&(m / 60 / 24)]),
+    }
+}
 
-        match rx.recv().await {
-            Some(c) => {
-                app.is_load = false;
-                app.objects = c.objects.clone();
-                app.objects_items = c.objects_items.clone();
+/// The `cache` subcommand, prints to the terminal instead of starting the interface
+fn run_cache_command(
+    command: CacheCommand,
+    cache_ttl: Duration,
+    article_ttl: Duration,
+) -> Result<(), StoreError> {
+    let articles = store::article_entries()?;
+    let expired = |e: &&store::Entry| is_expired(e.age, article_ttl);
+
+    match command {
+        CacheCommand::Stats => {
+            match store::catalog_entry()? {
+                Some((count, age)) => println!(
+                    "{} {}",
+                    fill(messages().cache_catalog, &[&count, &format_age(age)]),
+                    if is_expired(age, cache_ttl) {
+                        messages().cache_expired
+                    } else {
+                        ""
+                    }
+                ),
+                None => println!("{}", messages().cache_no_catalog),
             }
-            None => {
-                rx.close();
+
+            let size: u64 = articles.iter().map(|e| e.size).sum();
+            let raw_size: u64 = articles.iter().map(|e| e.raw_size).sum();
+            println!(
+                "{}",
+                fill(
+                    messages().cache_articles,
+                    &[&articles.len(), &articles.iter().filter(expired).count()]
+                )
+            );
+            println!(
+                "{}",
+                fill(
+                    messages().cache_compression,
+                    &[
+                        &format_size(raw_size),
+                        &format_size(size),
+                        &(size * 100).checked_div(raw_size).unwrap_or(100)
+                    ]
+                )
+            );
+            match store::dictionary_size()? {
+                Some(size) => println!(
+                    "{}",
+                    fill(messages().cache_dictionary, &[&format_size(size)])
+                ),
+                None => println!("{}", messages().cache_no_dictionary),
             }
         }
 
-        let timeout = tick_rate
-            .checked_sub(last_tick.elapsed())
-            .unwrap_or_else(|| Duration::from_secs(0));
-
-        if crossterm::event::poll(timeout)? {
-            if let Event::Key(key) = event::read()? {
-                match app.mode {
-                    Mode::Default => match key.code {
-                        KeyCode::Esc => {
-                            return Ok(());
-                        }
+        CacheCommand::List => {
+            for entry in &articles {
+                println!(
+                    "{:<32} {:>10} {:>10} {:>4} {:>8} {}",
+                    entry.page_id,
+                    format_size(entry.raw_size),
+                    format_size(entry.size),
+                    entry.revisions,
+                    format_age(entry.age),
+                    if expired(&entry) {
+                        messages().cache_expired
+                    } else {
+                        ""
+                    }
+                );
+            }
+        }
 
-                        KeyCode::Right | KeyCode::Left => {
-                            if app.mode == Mode::Default {
-                                if WindowSelect::eq(&app.window, &WindowSelect::Explorer) {
-                                    app.window = WindowSelect::Objects;
-                                } else {
-                                    app.window = WindowSelect::Explorer;
-                                }
-                            }
-                        }
+        CacheCommand::Prune => {
+            let pages: Vec<&str> = articles
+                .iter()
+                .filter(expired)
+                .map(|e| e.page_id.as_str())
+                .collect();
+            store::remove_articles(&pages)?;
+            println!("{}", fill(messages().cache_pruned, &[&pages.len()]));
+        }
 
-                        KeyCode::Up => {
-                            if !app.is_load && app.window == WindowSelect::Objects {
-                                app.objects_items.previous()
-                            }
+        CacheCommand::Clear => {
+            store::clear()?;
+            println!("{}", messages().cache_cleared);
+        }
+    }
 
-                            if !app.is_load && app.window == WindowSelect::Explorer {
-                                let mut scroll = app.scroll;
-                                if scroll.0 != 0 {
-                                    scroll.0 -= 1;
-                                }
+    Ok(())
+}
 
-                                app.scroll = scroll;
-                            }
-                        }
+/// The `mirror` subcommand, the objects are scraped first unless they are cached
+async fn run_mirror(app: &AppStates) {
+    let objects = parse_all(&app.site, app.cache_ttl).await.objects;
+    if objects.is_empty() {
+        eprintln!("{}", messages().mirror_no_objects);
+        return;
+    }
 
-                        KeyCode::Down => {
-                            if !app.is_load && app.window == WindowSelect::Objects {
-                                app.objects_items.next()
-                            }
+    let report = mirror(&app.site, &objects, app.article_ttl, |i, total, page| {
+        println!("[{}/{}] {}", i, total, page)
+    })
+    .await;
 
-                            if !app.is_load && app.window == WindowSelect::Explorer {
-                                let mut scroll = app.scroll;
-                                scroll.0 += 1;
-                                app.scroll = scroll;
-                            }
-                        }
+    for page in &report.failed {
+        eprintln!("{}", fill(messages().load_failed, &[page]));
+    }
+    println!(
+        "{}",
+        fill(
+            messages().mirror_done,
+            &[&report.loaded, &report.skipped, &report.failed.len()]
+        )
+    );
+}
 
-                        KeyCode::PageDown => {
-                            if !app.is_load && app.window == WindowSelect::Objects {
-                                app.objects_items.select_last();
-                            }
-                        }
+/// Adds the selected object or the open article to the favorites or removes it
+fn toggle_favorite(app: &mut AppStates) {
+    let page_id = match app.window {
+        WindowSelect::Explorer => app.explorer.current().map(|a| a.page_id.clone()),
+        _ => app.objects_items.selected().map(|o| o.get_page_id()),
+    };
+    let Some(page_id) = page_id else {
+        return;
+    };
 
-                        KeyCode::PageUp => {
-                            if !app.is_load && app.window == WindowSelect::Objects {
-                                app.objects_items.select_first();
-                            }
-                        }
+    app.status = Some(match store::toggle_favorite(&page_id) {
+        Ok(true) => {
+            app.favorites.insert(page_id.clone());
+            fill(messages().favorite_added, &[&page_id])
+        }
+        Ok(false) => {
+            app.favorites.remove(&page_id);
+            fill(messages().favorite_removed, &[&page_id])
+        }
+        Err(e) => fill(messages().save_failed, &[&e]),
+    });
+}
 
-                        KeyCode::Char(c) => {
-                            app.mode = Mode::Search;
-                            app.objects_items.unselect();
-                            app.search.push(c);
-                        }
+/// Loads the objects of the next branch of [`BRANCHES`], starting with the one of the config
+fn switch_branch(app: &mut AppStates, loader: &Sender<Catalog>) {
+    if app.is_load {
+        return;
+    }
 
-                        KeyCode::Backspace => {
-                            app.mode = Mode::Search;
-                            app.search.pop();
-                        }
+    let mut branches = vec![app.cached_branch.as_str()];
+    branches.extend(BRANCHES.iter().filter(|b| **b != app.cached_branch));
+    let next = branches
+        .iter()
+        .position(|b| *b == app.site.branch)
+        .map_or(0, |i| (i + 1) % branches.len());
 
-                        KeyCode::Enter => {
-                            if app.window == WindowSelect::Objects {
-                                app.scroll = (0, 0);
-
-                                let i = app.objects_items.get_selected_id();
-                                let u = app.objects_items.items.get(i);
-                                if u.is_some() {
-                                    let r = parse_object_page(u.unwrap().get_id().as_str()).await;
-
-                                    if r.is_none() {
-                                        app.explorer = Some(String::from("None"));
-                                    } else {
-                                        app.explorer = Some(r.unwrap().source);
-                                    }
+    app.site.branch = branches[next].to_string();
+    app.status = Some(fill(messages().branch_switched, &[&app.site.branch]));
+    app.is_load = true;
+    load_objects(app, loader.clone(), false);
+}
 
-                                    app.window = WindowSelect::Explorer;
-                                }
-                            }
-                        }
+/// Writes the whole store into [`EXPORT_FILE`], like the `export` command
+fn export(app: &mut AppStates) {
+    let path = Path::new(EXPORT_FILE);
+
+    app.status = Some(match archive::export(path, false) {
+        Ok(snapshot) => fill(
+            messages().export_done,
+            &[
+                &snapshot.objects.len(),
+                &snapshot.articles.len(),
+                &snapshot.favorites.len(),
+                &path.display(),
+            ],
+        ),
+        Err(e) => fill(messages().save_failed, &[&e]),
+    });
+}
 
-                        _ => {}
-                    },
+/// Loads the active article from the site again, keeping where it was scrolled to
+async fn refresh_article(app: &mut AppStates) {
+    let Some((page_id, scroll)) = app
+        .explorer
+        .current()
+        .map(|a| (a.page_id.clone(), a.scroll))
+    else {
+        return;
+    };
 
-                    Mode::Search => match key.code {
-                        KeyCode::Esc => {
-                            app.mode = Mode::Default;
-                        }
+    match fetch_page(&app.site, &page_id).await {
+        Some(r) => {
+            app.explorer.open(Article::from(r));
+            if let Some(article) = app.explorer.current_mut() {
+                article.scroll = scroll;
+            }
+            app.status = Some(String::from(messages().article_refreshed));
+        }
+        None => app.status = Some(fill(messages().load_failed, &[&page_id])),
+    }
+}
 
-                        KeyCode::Char(c) => {
-                            app.search.push(c);
-                            search(app)
-                        }
+/// Follows a link of the opened article
+async fn open_link(app: &mut AppStates, link: usize, new_tab: bool) {
+    let Some(page) = app
+        .explorer
+        .current()
+        .and_then(|a| a.links.get(link))
+        .map(|l| l.page.clone())
+    else {
+        return;
+    };
 
-                        KeyCode::Backspace => {
-                            app.search.pop();
-                            search(app);
-                        }
+    let result = parse_page(&app.site, &page, app.article_ttl).await;
+    show_article(app, result, page, new_tab);
+}
 
-                        KeyCode::Enter => {
-                            app.mode = Mode::Default;
-                            app.window = WindowSelect::Objects;
-                            search(app);
-                            if !app.is_load {
-                                app.objects_items.next();
-                            }
-                        }
+/**
+Selects the object in the list, dropping the search and filters if they hide
+it. Objects missing from the catalog are fetched and opened right away.
+*/
+async fn jump_to(app: &mut AppStates, id: String) {
+    if app.objects_items.select_where(|o| o.has_id(&id)) {
+        app.window = WindowSelect::Objects;
+        return;
+    }
 
-                        KeyCode::Right | KeyCode::Left => {
-                            app.mode = Mode::Default;
-                            if WindowSelect::eq(&app.window, &WindowSelect::Explorer) {
-                                app.window = WindowSelect::Objects;
-                            } else {
-                                app.window = WindowSelect::Explorer;
-                            }
-                        }
+    if app.objects_items.all().iter().any(|o| o.has_id(&id)) {
+        app.search.clear();
+        app.filters.clear();
+        search(app);
+        app.objects_items.select_where(|o| o.has_id(&id));
+        app.window = WindowSelect::Objects;
+        return;
+    }
 
-                        KeyCode::Down => {
-                            app.mode = Mode::Default;
-                            app.window = WindowSelect::Objects;
-                            app.objects_items.next();
-                        }
+    if !open_article(app, id.clone(), false).await {
+        app.status = Some(fill(messages().object_not_found, &[&id.to_uppercase()]));
+    }
+}
 
-                        _ => {}
-                    },
-                }
+/**
+Selects the next object matching the `Find` query without filtering the
+list, or moves to the next match in the article when the explorer is focused
+*/
+fn find_next(app: &mut AppStates, forward: bool) {
+    if app.window == WindowSelect::Explorer {
+        if let Some(article) = app.explorer.current_mut() {
+            if article.next_match(forward).is_none() {
+                app.status = Some(String::from(messages().no_matches_in_article));
             }
+            return;
         }
+    }
 
-        if last_tick.elapsed() >= tick_rate {
-            last_tick = Instant::now();
-        }
+    if app.is_load || app.find.is_empty() {
+        return;
+    }
+
+    let query = Query::parse(&app.find);
+    app.window = WindowSelect::Objects;
+
+    if !app
+        .objects_items
+        .select_next_where(|o| query.matches(o), forward)
+    {
+        app.status = Some(fill(messages().not_found, &[&app.find]));
     }
 }
 
-fn ui<B: Backend>(f: &mut Frame<B>, app: &mut AppStates) {
-    let size = f.size();
+async fn run_command(app: &mut AppStates) {
+    match Command::parse(&app.input) {
+        Ok(Command::Jump(id)) => jump_to(app, id).await,
+        Err(e) => app.status = Some(e.to_string()),
+    }
+}
 
-    let vertical_chunks = Layout::default()
-        .direction(Direction::Vertical)
-        .constraints([Constraint::Percentage(90), Constraint::Percentage(10)].as_ref())
-        .split(size);
+fn move_in_list<T>(list: &mut StatefulList<T>, action: Action) {
+    match action {
+        Action::Up => list.previous(),
+        Action::Down => list.next(),
+        Action::PageUp => list.page_up(),
+        Action::PageDown => list.page_down(),
+        Action::HalfPageUp => list.half_page_up(),
+        Action::HalfPageDown => list.half_page_down(),
+        Action::First => list.select_first(),
+        Action::Last => list.select_last(),
+        _ => {}
+    }
+}
+
+/// Scrolls the active article, and the compared one too unless they scroll separately
+fn scroll_explorer(app: &mut AppStates, delta: isize) {
+    if let Some(article) = app.explorer.current_mut() {
+        article.scroll_by(delta);
+    }
 
-    let mut chunks = Layout::default().direction(Direction::Horizontal);
+    if !app.explorer.independent_scroll {
+        if let Some(other) = app.explorer.other_mut() {
+            other.scroll_by(delta);
+        }
+    }
+}
 
+/// Opens the selected object, or follows the selected link of the article
+async fn open_selected(app: &mut AppStates, new_tab: bool) {
     if app.window == WindowSelect::Objects {
-        chunks =
-            chunks.constraints([Constraint::Percentage(60), Constraint::Percentage(50)].as_ref());
-    } else {
-        chunks =
-            chunks.constraints([Constraint::Percentage(20), Constraint::Percentage(80)].as_ref());
+        if let Some(id) = app.objects_items.selected().map(|o| o.get_id()) {
+            open_article(app, id, new_tab).await;
+        }
+    } else if app.window == WindowSelect::Explorer {
+        if let Some(link) = app.explorer.current().and_then(|a| a.selected_link) {
+            open_link(app, link, new_tab).await;
+        }
     }
+}
 
-    let chunks = chunks.split(vertical_chunks[0]);
+async fn handle_action(app: &mut AppStates, action: Action, loader: &Sender<Catalog>) {
+    match action {
+        Action::Quit => {
+            app.quit = true;
+        }
 
-    let chunk_left = Layout::default()
-        .direction(Direction::Vertical)
-        .constraints([Constraint::Percentage(12), Constraint::Percentage(100)])
-        .split(chunks[0]);
+        Action::NextWindow => switch_window(app, true),
+
+        Action::PreviousWindow => switch_window(app, false),
+
+        Action::Up
+        | Action::Down
+        | Action::PageUp
+        | Action::PageDown
+        | Action::HalfPageUp
+        | Action::HalfPageDown
+        | Action::First
+        | Action::Last => match app.window {
+            WindowSelect::Searches => move_in_list(&mut app.saved_searches, action),
+
+            WindowSelect::Objects if !app.is_load => move_in_list(&mut app.objects_items, action),
+
+            WindowSelect::Explorer => {
+                if let Some(article) = app.explorer.current() {
+                    let page = article.viewport as isize;
+                    scroll_explorer(
+                        app,
+                        match action {
+                            Action::Up => -1,
+                            Action::Down => 1,
+                            Action::PageUp => -page,
+                            Action::PageDown => page,
+                            Action::HalfPageUp => -(page / 2).max(1),
+                            Action::HalfPageDown => (page / 2).max(1),
+                            Action::First => isize::MIN,
+                            _ => isize::MAX,
+                        },
+                    );
+                }
+            }
 
-    let mut block_with_scp = Block::default().borders(Borders::ALL).title("SCP Объекты");
-    let mut block_explorer = Block::default().borders(Borders::ALL).title("Обзор");
+            _ => {}
+        },
 
-    let block_info = Block::default().borders(Borders::ALL);
-    let text: Vec<Spans>;
+        Action::Open => {
+            if app.window == WindowSelect::Searches {
+                if let Some(saved) = app.saved_searches.selected() {
+                    app.search = saved.query.clone();
+                    app.window = WindowSelect::Objects;
+                    search(app);
+                }
 
-    match app.window {
-        WindowSelect::Explorer => {
-            text = vec![Spans::from(vec![
-                Span::raw("  "),
-                Span::styled("Esc", Style::default().fg(Color::Green)),
-                Span::raw(" "),
-                Span::styled("Выйти", Style::default().add_modifier(Modifier::BOLD)),
-                Span::raw("  "),
-                Span::styled("<- ->", Style::default().fg(Color::Green)),
-                Span::raw(" "),
-                Span::styled(
-                    "Выбрать окно",
-                    Style::default().add_modifier(Modifier::BOLD),
-                ),
-            ])];
-        }
-        WindowSelect::Objects => {
-            text = vec![Spans::from(vec![
-                Span::raw("  "),
-                Span::styled("Esc", Style::default().fg(Color::Green)),
-                Span::raw(" "),
-                Span::styled("Выйти", Style::default().add_modifier(Modifier::BOLD)),
-                Span::raw("  "),
-                Span::styled("<- ->", Style::default().fg(Color::Green)),
-                Span::raw(" "),
-                Span::styled(
-                    "Выбрать окно",
-                    Style::default().add_modifier(Modifier::BOLD),
-                ),
-                Span::raw(" "),
-                Span::styled("PgUp PgDown", Style::default().fg(Color::Green)),
-                Span::raw(" "),
-                Span::styled(
-                    "Выбрать первый или последний объект",
-                    Style::default().add_modifier(Modifier::BOLD),
-                ),
-            ])];
+                return;
+            }
+
+            open_selected(app, false).await;
         }
-    }
 
-    let info = Paragraph::new(text)
-        .alignment(Alignment::Left)
-        .wrap(Wrap { trim: false })
-        .block(block_info);
+        Action::OpenInNewTab => open_selected(app, true).await,
 
-    if app.mode == Mode::Default {
-        if WindowSelect::eq(&app.window, &WindowSelect::Explorer) {
-            block_explorer = block_explorer.border_style(Style::default().bg(Color::Blue));
-        } else {
-            block_with_scp = block_with_scp.border_style(Style::default().bg(Color::Blue));
+        Action::ToggleFavorite => toggle_favorite(app),
+
+        Action::ToggleTheme => {
+            app.theme_name = app.theme_name.next();
+            // The colors were checked when the config was loaded
+            app.theme = Theme::with_colors(app.theme_name, &app.colors)
+                .unwrap_or_else(|_| Theme::new(app.theme_name));
         }
-    }
 
-    let mut search_block = Block::default()
-        .title("Поиск")
-        .border_type(tui::widgets::BorderType::Rounded)
-        .borders(Borders::ALL);
+        Action::SwitchBranch => switch_branch(app, loader),
+
+        Action::Export => export(app),
+
+        Action::Compare => {
+            if !app.explorer.toggle_compare() && app.explorer.len() < 2 {
+                app.status = Some(String::from(messages().compare_needs_two_tabs));
+            }
+        }
+
+        Action::ToggleSyncScroll => {
+            app.explorer.independent_scroll = !app.explorer.independent_scroll;
+            app.status = Some(String::from(if app.explorer.independent_scroll {
+                messages().independent_scroll
+            } else {
+                messages().sync_scroll
+            }));
+        }
+
+        Action::ToggleZen => {
+            app.zen = !app.zen;
+            if app.zen && !app.explorer.is_empty() {
+                app.window = WindowSelect::Explorer;
+            }
+        }
+
+        Action::NextTab => app.explorer.next(),
+
+        Action::PreviousTab => app.explorer.previous(),
+
+        Action::ScrollLeft | Action::ScrollRight => {
+            if app.window == WindowSelect::Explorer {
+                if let Some(article) = app.explorer.current_mut() {
+                    article.scroll_half_width(action == Action::ScrollRight);
+                }
+            }
+        }
+
+        Action::ToggleWrap => {
+            if let Some(article) = app.explorer.current_mut() {
+                article.toggle_wrap();
+            }
+        }
+
+        Action::NextLink | Action::PreviousLink => {
+            if app.window == WindowSelect::Explorer {
+                if let Some(article) = app.explorer.current_mut() {
+                    article.select_link(action == Action::NextLink);
+                }
+            }
+        }
+
+        Action::Refresh if app.window == WindowSelect::Explorer && !app.explorer.is_empty() => {
+            refresh_article(app).await
+        }
+
+        Action::Refresh => {
+            if !app.is_load {
+                app.is_load = true;
+                load_objects(app, loader.clone(), true);
+            }
+        }
+
+        Action::FilterSafe => toggle_class_filter(app, ClassificationScp::Safe),
+
+        Action::FilterEuclid => toggle_class_filter(app, ClassificationScp::Euclid),
+
+        Action::FilterKeter => toggle_class_filter(app, ClassificationScp::Keter),
+
+        Action::FilterThaumiel => toggle_class_filter(app, ClassificationScp::Thaumiel),
+
+        Action::FilterNeutralized => toggle_class_filter(app, ClassificationScp::Neutralized),
+
+        Action::FilterNonStandard => toggle_class_filter(app, ClassificationScp::NonStandard),
+
+        Action::FilterNone => toggle_class_filter(app, ClassificationScp::None),
+
+        Action::ClearFilters => {
+            app.filters.clear();
+            search(app);
+        }
+
+        Action::CycleSort => {
+            app.filters.sort = app.filters.sort.next();
+            search(app);
+        }
+
+        Action::ReverseSort => {
+            app.filters.reverse = !app.filters.reverse;
+            search(app);
+        }
+
+        Action::Command => {
+            app.input.clear();
+            app.mode = Mode::Command;
+        }
+
+        Action::Palette => {
+            app.palette = Palette::default();
+            app.mode = Mode::Palette;
+        }
+
+        Action::Search => {
+            app.mode = Mode::Search;
+        }
+
+        Action::Help => {
+            app.help = Overlay::default();
+            app.mode = Mode::Help;
+        }
+
+        Action::Find => {
+            app.input.clear();
+            app.mode = Mode::Find;
+        }
+
+        Action::NextMatch => find_next(app, true),
+
+        Action::PreviousMatch => find_next(app, false),
+
+        Action::Close => {
+            if app.window == WindowSelect::Explorer && !app.explorer.is_empty() {
+                app.explorer.close();
+                if app.explorer.is_empty() {
+                    app.window = WindowSelect::Objects;
+                }
+            } else {
+                app.quit = true;
+            }
+        }
+
+        Action::SaveSearch => {
+            if !app.search.trim().is_empty() {
+                app.input.clear();
+                app.mode = Mode::SaveSearch;
+            }
+        }
+
+        Action::DeleteSearch => {
+            if app.window != WindowSelect::Searches {
+                return;
+            }
+
+            if let Some(i) = app.saved_searches.selected_index() {
+                app.saved_searches.remove(i);
+                save_searches(app);
+                count_saved_searches(app);
+
+                if app.saved_searches.is_empty() {
+                    app.window = WindowSelect::Objects;
+                }
+            }
+        }
+    }
+}
+
+fn contains(area: Rect, x: u16, y: u16) -> bool {
+    x >= area.x && x < area.right() && y >= area.y && y < area.bottom()
+}
+
+/// Row inside the borders of `area`, `None` on the borders themselves
+fn inner_row(area: Rect, y: u16) -> Option<usize> {
+    (y > area.y && y + 1 < area.bottom()).then(|| (y - area.y - 1) as usize)
+}
+
+/// Link of the active article under the cursor, `area` is where it is drawn
+fn link_at(app: &AppStates, area: Rect, x: u16, y: u16) -> Option<usize> {
+    let article = app.explorer.current()?;
+    let row = inner_row(area, y)?;
+    let column = x.checked_sub(area.x + 1)? as usize;
+
+    article.link_at(
+        article.scroll.0 as usize + row,
+        article.scroll.1 as usize + column,
+    )
+}
+
+/// Labels of the tab bar, the page ids are short enough to fit several tabs
+fn tab_labels(tabs: &Tabs) -> Vec<String> {
+    tabs.iter()
+        .map(|a| format!(" {} ", a.page_id.to_uppercase()))
+        .collect()
+}
+
+/// Tab under the column `x` of the tab bar, tabs are divided by one column
+fn tab_at(tabs: &Tabs, x: u16) -> Option<usize> {
+    let mut start = 0;
+
+    for (i, label) in tab_labels(tabs).iter().enumerate() {
+        let end = start + label.chars().count();
+        if (start..end).contains(&(x as usize)) {
+            return Some(i);
+        }
+        start = end + 1;
+    }
+
+    None
+}
+
+/**
+A click focuses the pane under the cursor and selects the clicked row of a
+list, a click on the selected row opens it. In the explorer a click follows
+the link under the cursor. The wheel scrolls whatever is under the cursor.
+*/
+async fn handle_mouse(app: &mut AppStates, mouse: MouseEvent) {
+    if app.mode == Mode::Help {
+        match mouse.kind {
+            MouseEventKind::ScrollUp => app.help.scroll_by(-3),
+            MouseEventKind::ScrollDown => app.help.scroll_by(3),
+            _ => {}
+        }
+        return;
+    }
+
+    if !matches!(app.mode, Mode::Default | Mode::Search) {
+        return;
+    }
+
+    let (x, y) = (mouse.column, mouse.row);
+    let panes = app.panes.clone();
+
+    match mouse.kind {
+        MouseEventKind::Down(MouseButton::Left) => {
+            app.mode = Mode::Default;
+
+            if contains(panes.objects, x, y) {
+                let focused = app.window == WindowSelect::Objects;
+                app.window = WindowSelect::Objects;
+                if app.is_load {
+                    return;
+                }
+
+                let Some(i) =
+                    inner_row(panes.objects, y).and_then(|r| app.objects_items.item_at_row(r))
+                else {
+                    return;
+                };
+
+                if focused && app.objects_items.selected_index() == Some(i) {
+                    if let Some(id) = app.objects_items.selected().map(|o| o.get_id()) {
+                        open_article(app, id, false).await;
+                    }
+                } else {
+                    app.objects_items.select(i);
+                }
+            } else if contains(panes.searches, x, y) {
+                let focused = app.window == WindowSelect::Searches;
+                app.window = WindowSelect::Searches;
+
+                let Some(i) =
+                    inner_row(panes.searches, y).and_then(|r| app.saved_searches.item_at_row(r))
+                else {
+                    return;
+                };
+
+                if focused && app.saved_searches.selected_index() == Some(i) {
+                    if let Some(saved) = app.saved_searches.selected() {
+                        app.search = saved.query.clone();
+                        app.window = WindowSelect::Objects;
+                        search(app);
+                    }
+                } else {
+                    app.saved_searches.select(i);
+                }
+            } else if contains(panes.tab_bar, x, y) {
+                app.window = WindowSelect::Explorer;
+
+                if let Some(i) = tab_at(&app.explorer, x - panes.tab_bar.x) {
+                    app.explorer.select(i);
+                }
+            } else if contains(panes.explorer, x, y) || contains(panes.compare, x, y) {
+                app.window = WindowSelect::Explorer;
+
+                // A click on the other side of the comparison makes it active
+                let area = if contains(panes.compare, x, y) {
+                    app.explorer.next();
+                    panes.compare
+                } else {
+                    panes.explorer
+                };
+
+                if let Some(link) = link_at(app, area, x, y) {
+                    open_link(app, link, false).await;
+                }
+            }
+        }
+
+        // The middle button opens links in a new tab like in a browser
+        MouseEventKind::Down(MouseButton::Middle) if contains(panes.explorer, x, y) => {
+            app.mode = Mode::Default;
+            app.window = WindowSelect::Explorer;
+
+            if let Some(link) = link_at(app, panes.explorer, x, y) {
+                open_link(app, link, true).await;
+            }
+        }
+
+        MouseEventKind::ScrollUp | MouseEventKind::ScrollDown => {
+            let down = mouse.kind == MouseEventKind::ScrollDown;
+
+            let delta = if down { 3 } else { -3 };
+
+            if contains(panes.explorer, x, y) {
+                scroll_explorer(app, delta);
+            } else if contains(panes.compare, x, y) {
+                if let Some(other) = app.explorer.other_mut() {
+                    other.scroll_by(delta);
+                }
+                if !app.explorer.independent_scroll {
+                    if let Some(article) = app.explorer.current_mut() {
+                        article.scroll_by(delta);
+                    }
+                }
+            } else if contains(panes.objects, x, y) && !app.is_load {
+                move_in_list(
+                    &mut app.objects_items,
+                    if down { Action::Down } else { Action::Up },
+                );
+            } else if contains(panes.searches, x, y) {
+                move_in_list(
+                    &mut app.saved_searches,
+                    if down { Action::Down } else { Action::Up },
+                );
+            }
+        }
+
+        _ => {}
+    }
+}
+
+async fn run_app<B: Backend>(
+    terminal: &mut Terminal<B>,
+    app: &mut AppStates,
+    tick_rate: Duration,
+    loader: Sender<Catalog>,
+    mut objects: Receiver<Catalog>,
+) -> io::Result<()> {
+    let mut last_tick = Instant::now();
+
+    loop {
+        terminal.draw(|f| ui(f, app))?;
+
+        if let Ok(catalog) = objects.try_recv() {
+            app.is_load = false;
+            if catalog.stale {
+                app.status = Some(String::from(messages().objects_stale));
+            }
+
+            // A failed refresh brings nothing, the objects already listed stay
+            if !catalog.objects.is_empty() || !catalog.stale {
+                app.objects_items.set_items(Arc::new(catalog.objects));
+                search(app);
+                count_saved_searches(app);
+            }
+        }
+
+        let timeout = tick_rate
+            .checked_sub(last_tick.elapsed())
+            .unwrap_or_else(|| Duration::from_secs(0));
+
+        if crossterm::event::poll(timeout)? {
+            let event = event::read()?;
+
+            if let Event::Mouse(mouse) = event {
+                handle_mouse(app, mouse).await;
+            }
+
+            if let Event::Key(key) = event {
+                app.status = None;
+
+                match app.mode {
+                    Mode::Default => match app.keymap.lookup(&key) {
+                        Lookup::Action(action) => handle_action(app, action, &loader).await,
+
+                        Lookup::Pending => {}
+
+                        Lookup::Unbound if app.keymap.is_modal() => {}
+
+                        Lookup::Unbound => match key.code {
+                            KeyCode::Char(c)
+                                if (key.modifiers - KeyModifiers::SHIFT).is_empty() =>
+                            {
+                                app.mode = Mode::Search;
+                                app.objects_items.unselect();
+                                app.search.push(c);
+                            }
+
+                            KeyCode::Backspace => {
+                                app.mode = Mode::Search;
+                                app.search.pop();
+                            }
+
+                            _ => {}
+                        },
+                    },
+
+                    Mode::Search => match key.code {
+                        KeyCode::Esc => {
+                            app.mode = Mode::Default;
+                        }
+
+                        KeyCode::Char(c) => {
+                            app.search.push(c);
+                            search(app)
+                        }
+
+                        KeyCode::Backspace => {
+                            app.search.pop();
+                            search(app);
+                        }
+
+                        KeyCode::Enter => {
+                            app.mode = Mode::Default;
+                            app.window = WindowSelect::Objects;
+                            search(app);
+                            if !app.is_load {
+                                app.objects_items.next();
+                            }
+                        }
+
+                        KeyCode::Right | KeyCode::Left => {
+                            app.mode = Mode::Default;
+                            switch_window(app, key.code == KeyCode::Right);
+                        }
+
+                        KeyCode::Down => {
+                            app.mode = Mode::Default;
+                            app.window = WindowSelect::Objects;
+                            app.objects_items.next();
+                        }
+
+                        _ => {}
+                    },
+
+                    Mode::SaveSearch => match key.code {
+                        KeyCode::Esc => {
+                            app.mode = Mode::Default;
+                        }
+
+                        KeyCode::Char(c) => {
+                            app.input.push(c);
+                        }
+
+                        KeyCode::Backspace => {
+                            app.input.pop();
+                        }
+
+                        KeyCode::Enter => {
+                            save_search(app);
+                            app.mode = Mode::Default;
+                        }
+
+                        _ => {}
+                    },
+
+                    Mode::Command => match key.code {
+                        KeyCode::Esc => {
+                            app.mode = Mode::Default;
+                        }
+
+                        KeyCode::Char(c) => {
+                            app.input.push(c);
+                        }
+
+                        KeyCode::Backspace => {
+                            app.input.pop();
+                        }
+
+                        KeyCode::Enter => {
+                            app.mode = Mode::Default;
+                            if !app.is_load {
+                                run_command(app).await;
+                            }
+                        }
+
+                        _ => {}
+                    },
+
+                    Mode::Find => match key.code {
+                        KeyCode::Esc => {
+                            app.mode = Mode::Default;
+                        }
+
+                        KeyCode::Char(c) => {
+                            app.input.push(c);
+                        }
+
+                        KeyCode::Backspace => {
+                            app.input.pop();
+                        }
+
+                        KeyCode::Enter => {
+                            app.mode = Mode::Default;
+                            app.find = app.input.clone();
+
+                            match (&app.window, app.explorer.current_mut()) {
+                                (WindowSelect::Explorer, Some(article)) => {
+                                    if article.find(&app.find) > 0 {
+                                        article.next_match(true);
+                                    } else if !app.find.is_empty() {
+                                        app.status = Some(fill(messages().not_found, &[&app.find]));
+                                    }
+                                }
+                                _ => find_next(app, true),
+                            }
+                        }
+
+                        _ => {}
+                    },
+
+                    Mode::Help => match app.keymap.lookup(&key) {
+                        Lookup::Action(Action::Up) => app.help.scroll_by(-1),
+                        Lookup::Action(Action::Down) => app.help.scroll_by(1),
+                        Lookup::Action(Action::PageUp) => {
+                            app.help.scroll_by(-(app.help.viewport as isize))
+                        }
+                        Lookup::Action(Action::PageDown) => {
+                            app.help.scroll_by(app.help.viewport as isize)
+                        }
+                        Lookup::Action(Action::HalfPageUp) => {
+                            app.help.scroll_by(-(app.help.viewport as isize / 2))
+                        }
+                        Lookup::Action(Action::HalfPageDown) => {
+                            app.help.scroll_by(app.help.viewport as isize / 2)
+                        }
+                        Lookup::Action(Action::First) => app.help.scroll = 0,
+                        Lookup::Action(Action::Last) => app.help.scroll_by(isize::MAX),
+                        Lookup::Action(Action::Help | Action::Close | Action::Quit) => {
+                            app.mode = Mode::Default;
+                        }
+                        _ if key.code == KeyCode::Esc => app.mode = Mode::Default,
+                        _ => {}
+                    },
+
+                    Mode::Palette => match key.code {
+                        KeyCode::Esc => {
+                            app.mode = Mode::Default;
+                        }
+
+                        KeyCode::Up => app.palette.items.previous(),
+
+                        KeyCode::Down => app.palette.items.next(),
+
+                        KeyCode::Char(c) => {
+                            app.palette.input.push(c);
+                            app.palette.filter(&app.keymap);
+                        }
+
+                        KeyCode::Backspace => {
+                            app.palette.input.pop();
+                            app.palette.filter(&app.keymap);
+                        }
+
+                        KeyCode::Enter => {
+                            app.mode = Mode::Default;
+                            if let Some(action) = app.palette.items.selected().copied() {
+                                handle_action(app, action, &loader).await;
+                            }
+                        }
+
+                        _ => {}
+                    },
+                }
+            }
+        }
+
+        if app.quit {
+            return Ok(());
+        }
+
+        if last_tick.elapsed() >= tick_rate {
+            last_tick = Instant::now();
+        }
+    }
+}
+
+fn ui<B: Backend>(f: &mut Frame<B>, app: &mut AppStates) {
+    let size = f.size();
+
+    let areas = layout::areas(
+        size,
+        &LayoutState {
+            explorer_focused: app.window == WindowSelect::Explorer,
+            zen: app.zen,
+            prompt: matches!(
+                app.mode,
+                Mode::Search | Mode::SaveSearch | Mode::Command | Mode::Find
+            ),
+            saved_searches: app.saved_searches.len(),
+        },
+    );
+
+    let m = messages();
+    let filters = app.filters.describe();
+    let mut block_with_scp = Block::default()
+        .borders(Borders::ALL)
+        .title(if filters.is_empty() {
+            String::from(m.objects)
+        } else {
+            format!("{} {}", m.objects, filters)
+        });
+    // The title of an open article is set when it is rendered
+    let mut block_explorer = Block::default().borders(Borders::ALL).title(m.explorer);
+    let mut block_searches = Block::default()
+        .borders(Borders::ALL)
+        .title(m.saved_searches);
+
+    let mut title: Vec<Span> = Vec::new();
+    if parsing::is_offline() {
+        title.push(Span::styled(format!("[{}] ", m.offline), app.theme.heading));
+    }
+    if let Some(status) = &app.status {
+        title.push(Span::styled(status.as_str(), app.theme.status));
+    }
+    let block_info = Block::default()
+        .borders(Borders::ALL)
+        .title(Spans::from(title));
+
+    let mut hints: Vec<Span> = Vec::new();
+    for (keys, label) in status_hints(app) {
+        hints.push(Span::raw("  "));
+        hints.push(Span::styled(keys, app.theme.key));
+        hints.push(Span::raw(" "));
+        hints.push(Span::styled(label, app.theme.label));
+    }
+    let text = Spans::from(hints);
+
+    let info = Paragraph::new(text)
+        .alignment(Alignment::Left)
+        .block(block_info);
+
+    if app.mode == Mode::Default {
+        match app.window {
+            WindowSelect::Explorer => {
+                block_explorer = block_explorer.border_style(app.theme.focus);
+            }
+            WindowSelect::Objects => {
+                block_with_scp = block_with_scp.border_style(app.theme.focus);
+            }
+            WindowSelect::Searches => {
+                block_searches = block_searches.border_style(app.theme.focus);
+            }
+        }
+    }
+
+    let mut search_block = Block::default()
+        .title(m.search)
+        .border_type(tui::widgets::BorderType::Rounded)
+        .borders(Borders::ALL);
 
     if app.mode == Mode::Search {
-        search_block = search_block.border_style(Style::default().bg(Color::Blue));
+        search_block = search_block.border_style(app.theme.focus);
     }
 
-    let search_widget = Paragraph::new(Span::styled(
-        &app.search,
-        Style::default().fg(Color::LightGreen),
-    ))
-    .block(search_block);
+    let search_widget = if app.mode == Mode::SaveSearch {
+        Paragraph::new(Span::styled(&app.input, app.theme.input)).block(
+            search_block
+                .title(fill(m.save_search_as, &[&app.search.trim()]))
+                .border_style(app.theme.focus),
+        )
+    } else if app.mode == Mode::Find {
+        Paragraph::new(Span::styled(format!("/{}", app.input), app.theme.input)).block(
+            search_block
+                .title(
+                    if app.window == WindowSelect::Explorer && !app.explorer.is_empty() {
+                        m.find_in_article
+                    } else {
+                        m.find_in_list
+                    },
+                )
+                .border_style(app.theme.focus),
+        )
+    } else if app.mode == Mode::Command {
+        Paragraph::new(Span::styled(format!(":{}", app.input), app.theme.input)).block(
+            search_block
+                .title(m.jump_to_object)
+                .border_style(app.theme.focus),
+        )
+    } else {
+        Paragraph::new(Span::styled(&app.search, app.theme.input)).block(search_block)
+    };
+
+    let searches: Vec<ListItem> = app
+        .saved_searches
+        .iter()
+        .enumerate()
+        .map(|(i, s)| match app.saved_counts.get(i) {
+            Some(count) => ListItem::new(format!("{} ({})", s.name, count)),
+            None => ListItem::new(s.name.clone()),
+        })
+        .collect();
+
+    let searches_list = List::new(searches)
+        .block(block_searches)
+        .highlight_style(app.theme.selected)
+        .highlight_symbol("➤");
 
     let objects: Vec<ListItem> = app
         .objects_items
-        .items
         .iter()
         .map(|o| {
-            ListItem::new(format!(
-                "[{}] {} - {}",
-                o.get_class(),
-                o.get_document_name(),
-                o.get_name()
-            ))
-            .style(Style::default().fg(Color::White))
+            let page_id = o.get_page_id();
+            let mark = if app.favorites.contains(&page_id) {
+                Span::styled("★ ", app.theme.favorite)
+            } else if app.visited.contains(&page_id) {
+                Span::styled("• ", app.theme.muted)
+            } else {
+                Span::raw("  ")
+            };
+
+            ListItem::new(Spans::from(vec![
+                mark,
+                Span::styled(
+                    format!("[{}]", o.get_class()),
+                    app.theme.class(o.get_class()),
+                ),
+                Span::styled(
+                    format!(" {} - {}", o.get_document_name(), o.get_name()),
+                    app.theme.text,
+                ),
+            ]))
         })
         .collect();
 
     let scp_list = List::new(objects)
         .block(block_with_scp)
-        .highlight_style(
-            Style::default()
-                .fg(Color::Blue)
-                .add_modifier(Modifier::BOLD),
-        )
+        .highlight_style(app.theme.selected)
         .highlight_symbol("➤");
 
     /* Search Pane */
-    f.render_widget(search_widget, chunk_left[0]);
+    if areas.search.area() > 0 {
+        f.render_widget(search_widget, areas.search);
+    }
+    if areas.searches.area() > 0 {
+        app.saved_searches
+            .set_viewport(areas.searches.height.saturating_sub(2) as usize);
+        f.render_stateful_widget(searches_list, areas.searches, &mut app.saved_searches.state);
+    }
     // Render block with the SCP objects
-    if !app.is_load {
-        f.render_stateful_widget(scp_list, chunk_left[1], &mut app.objects_items.state);
+    if areas.objects.area() == 0 {
+        // Hidden in zen mode or on narrow terminals
+    } else if !app.is_load {
+        app.objects_items
+            .set_viewport(areas.objects.height.saturating_sub(2) as usize);
+        f.render_stateful_widget(scp_list, areas.objects, &mut app.objects_items.state);
     } else {
         let mut block = Block::default()
             .border_style(Style::default())
             .border_type(tui::widgets::BorderType::Rounded)
             .borders(Borders::ALL)
-            .title("SCP Объекты (Загружаются)");
+            .title(m.objects_loading);
         if app.window == WindowSelect::Objects && app.mode == Mode::Default {
-            block = block.border_style(Style::default().bg(Color::Blue))
+            block = block.border_style(app.theme.focus)
         }
 
-        f.render_widget(block, chunk_left[1]);
+        f.render_widget(block, areas.objects);
     }
 
-    if app.explorer.is_some() {
-        let explorer = Paragraph::new(app.explorer.clone().unwrap())
-            .block(block_explorer)
-            .wrap(Wrap { trim: false })
-            .scroll(app.scroll);
-        // Render block for explore objects
-        f.render_widget(explorer, chunks[1]);
+    // The tab bar takes the first row of the explorer when anything is open
+    let (tab_bar, chunk_explorer) = if app.explorer.is_empty() || areas.explorer.area() == 0 {
+        (Rect::default(), areas.explorer)
     } else {
-        // Render block for explore objects
-        f.render_widget(block_explorer, chunks[1]);
+        let split = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Length(1), Constraint::Min(0)])
+            .split(areas.explorer);
+        (split[0], split[1])
+    };
+
+    app.panes = Panes {
+        objects: areas.objects,
+        searches: areas.searches,
+        explorer: chunk_explorer,
+        compare: Rect::default(),
+        tab_bar,
+    };
+
+    if !app.explorer.is_empty() {
+        let active = app.explorer.active();
+        let mut spans: Vec<Span> = Vec::new();
+        for (i, label) in tab_labels(&app.explorer).into_iter().enumerate() {
+            if i > 0 {
+                spans.push(Span::styled("│", app.theme.muted));
+            }
+            spans.push(if i == active {
+                Span::styled(label, app.theme.active_tab)
+            } else if Some(i) == app.explorer.other() {
+                Span::styled(label, app.theme.compared_tab)
+            } else {
+                Span::raw(label)
+            });
+        }
+        f.render_widget(Paragraph::new(Spans::from(spans)), tab_bar);
+    }
+
+    match app.explorer.sides() {
+        Some((left, right)) => {
+            let halves = Layout::default()
+                .direction(Direction::Horizontal)
+                .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
+                .split(chunk_explorer);
+
+            let active = app.explorer.active();
+            for (i, area) in [(left, halves[0]), (right, halves[1])] {
+                let area = layout::reading_column(area, app.reading_width);
+                let mut block = Block::default().borders(Borders::ALL);
+                if i == active {
+                    app.panes.explorer = area;
+                    if app.mode == Mode::Default && app.window == WindowSelect::Explorer {
+                        block = block.border_style(app.theme.focus);
+                    }
+                } else {
+                    app.panes.compare = area;
+                }
+
+                if let Some(article) = app.explorer.get_mut(i) {
+                    render_article(f, article, &app.theme, area, block);
+                }
+            }
+        }
+        None if chunk_explorer.area() == 0 => {}
+        None => match app.explorer.current_mut() {
+            Some(article) => {
+                let area = layout::reading_column(chunk_explorer, app.reading_width);
+                app.panes.explorer = area;
+                render_article(f, article, &app.theme, area, block_explorer);
+            }
+            // Render block for explore objects
+            None => f.render_widget(block_explorer, chunk_explorer),
+        },
     }
 
     // Render block for see tips for using app
-    f.render_widget(info, vertical_chunks[1]);
+    if areas.footer.area() > 0 {
+        f.render_widget(info, areas.footer);
+    }
+
+    if app.mode == Mode::Palette {
+        render_palette(f, app);
+    }
+
+    if app.mode == Mode::Help {
+        render_help(f, app);
+    }
+}
+
+/// Actions of the focused pane, listed first in the help
+fn context_actions(app: &AppStates) -> (&'static str, &'static [Action]) {
+    match app.window {
+        WindowSelect::Objects => (
+            messages().objects_list,
+            &[
+                Action::Open,
+                Action::OpenInNewTab,
+                Action::Up,
+                Action::Down,
+                Action::PageUp,
+                Action::PageDown,
+                Action::First,
+                Action::Last,
+                Action::Search,
+                Action::Find,
+                Action::NextMatch,
+                Action::PreviousMatch,
+                Action::Command,
+                Action::SaveSearch,
+                Action::FilterSafe,
+                Action::FilterEuclid,
+                Action::FilterKeter,
+                Action::FilterThaumiel,
+                Action::FilterNeutralized,
+                Action::FilterNonStandard,
+                Action::FilterNone,
+                Action::ClearFilters,
+                Action::CycleSort,
+                Action::ReverseSort,
+                Action::ToggleFavorite,
+                Action::Refresh,
+            ],
+        ),
+        WindowSelect::Searches => (
+            messages().saved_searches,
+            &[
+                Action::Open,
+                Action::Up,
+                Action::Down,
+                Action::First,
+                Action::Last,
+                Action::DeleteSearch,
+            ],
+        ),
+        WindowSelect::Explorer => (
+            messages().explorer,
+            &[
+                Action::Up,
+                Action::Down,
+                Action::PageUp,
+                Action::PageDown,
+                Action::First,
+                Action::Last,
+                Action::ScrollLeft,
+                Action::ScrollRight,
+                Action::ToggleWrap,
+                Action::ToggleZen,
+                Action::Find,
+                Action::NextMatch,
+                Action::PreviousMatch,
+                Action::NextLink,
+                Action::PreviousLink,
+                Action::Open,
+                Action::OpenInNewTab,
+                Action::NextTab,
+                Action::PreviousTab,
+                Action::Compare,
+                Action::ToggleSyncScroll,
+                Action::ToggleFavorite,
+                Action::Refresh,
+                Action::Close,
+            ],
+        ),
+    }
+}
+
+/// Short hints for the bottom line: the first key of an action and a label
+fn status_hints(app: &AppStates) -> Vec<(String, &'static str)> {
+    let key = |action: Action| app.keymap.keys(action).first().map(|k| k.to_string());
+    let bound = |hints: &[(Action, &'static str)]| -> Vec<(String, &'static str)> {
+        hints
+            .iter()
+            .filter_map(|(action, label)| Some((key(*action)?, *label)))
+            .collect()
+    };
+    let fixed = |hints: &[(&str, &'static str)]| -> Vec<(String, &'static str)> {
+        hints.iter().map(|(k, l)| (k.to_string(), *l)).collect()
+    };
+
+    let m = messages();
+    let mut hints = match app.mode {
+        Mode::Default => match app.window {
+            WindowSelect::Objects => bound(&[
+                (Action::Open, m.hint_open),
+                (Action::Find, m.hint_find),
+                (Action::Command, m.hint_number),
+                (Action::CycleSort, m.hint_sort),
+                (Action::Palette, m.hint_commands),
+            ]),
+            WindowSelect::Searches => bound(&[
+                (Action::Open, m.hint_apply),
+                (Action::DeleteSearch, m.hint_delete),
+            ]),
+            WindowSelect::Explorer => bound(&[
+                (Action::ToggleWrap, m.hint_wrap),
+                (Action::Find, m.hint_find),
+                (Action::NextLink, m.hint_links),
+                (Action::Open, m.hint_follow),
+                (Action::NextTab, m.hint_tabs),
+                (Action::Close, m.hint_close),
+            ]),
+        },
+        Mode::Search => fixed(&[("Enter", m.hint_to_list), ("Esc", m.hint_done)]),
+        Mode::SaveSearch | Mode::Command | Mode::Find => {
+            fixed(&[("Enter", m.hint_confirm), ("Esc", m.hint_cancel)])
+        }
+        Mode::Palette => fixed(&[
+            ("↑ ↓", m.hint_choose),
+            ("Enter", m.hint_run),
+            ("Esc", m.hint_close),
+        ]),
+        Mode::Help => fixed(&[("↑ ↓", m.hint_scroll), ("Esc", m.hint_close)]),
+    };
+
+    if app.mode == Mode::Default {
+        hints.extend(bound(&[
+            (Action::Help, m.hint_help),
+            (Action::Quit, m.hint_quit),
+        ]));
+
+        // Presets without a quit key leave the app by closing the list
+        if key(Action::Quit).is_none() && app.window != WindowSelect::Explorer {
+            hints.extend(bound(&[(Action::Close, m.hint_quit)]));
+        }
+    }
+
+    hints
+}
+
+/// Rectangle of the given size in percents in the middle of `r`
+fn centered_rect(percent_x: u16, percent_y: u16, r: Rect) -> Rect {
+    let vertical = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Percentage((100 - percent_y) / 2),
+            Constraint::Percentage(percent_y),
+            Constraint::Percentage((100 - percent_y) / 2),
+        ])
+        .split(r);
+
+    Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([
+            Constraint::Percentage((100 - percent_x) / 2),
+            Constraint::Percentage(percent_x),
+            Constraint::Percentage((100 - percent_x) / 2),
+        ])
+        .split(vertical[1])[1]
+}
+
+fn render_palette<B: Backend>(f: &mut Frame<B>, app: &mut AppStates) {
+    let area = centered_rect(60, 60, f.size());
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(3), Constraint::Min(0)])
+        .split(area);
+
+    let input = Paragraph::new(Span::styled(
+        format!("> {}", app.palette.input),
+        app.theme.input,
+    ))
+    .block(
+        Block::default()
+            .title(messages().palette)
+            .borders(Borders::ALL)
+            .border_type(tui::widgets::BorderType::Rounded)
+            .border_style(app.theme.focus),
+    );
+
+    let width = chunks[1].width.saturating_sub(4) as usize;
+    let items: Vec<ListItem> = app
+        .palette
+        .items
+        .iter()
+        .map(|a| {
+            let keys: Vec<String> = app.keymap.keys(*a).iter().map(|k| k.to_string()).collect();
+            let keys = keys.join(" ");
+            let description = a.description();
+            let padding = width.saturating_sub(description.chars().count() + keys.chars().count());
+
+            ListItem::new(Spans::from(vec![
+                Span::raw(description),
+                Span::raw(" ".repeat(padding)),
+                Span::styled(keys, app.theme.key),
+            ]))
+        })
+        .collect();
+
+    let list = List::new(items)
+        .block(Block::default().borders(Borders::ALL))
+        .highlight_style(app.theme.selected)
+        .highlight_symbol("➤");
+
+    f.render_widget(Clear, area);
+    f.render_widget(input, chunks[0]);
+    app.palette
+        .items
+        .set_viewport(chunks[1].height.saturating_sub(2) as usize);
+    f.render_stateful_widget(list, chunks[1], &mut app.palette.items.state);
+}
+
+fn render_help<B: Backend>(f: &mut Frame<B>, app: &mut AppStates) {
+    let area = centered_rect(70, 80, f.size());
+    let (context, actions) = context_actions(app);
+
+    let mut text: Vec<Spans> = Vec::new();
+    for section in help::sections(&app.keymap, context, actions) {
+        if !text.is_empty() {
+            text.push(Spans::default());
+        }
+        text.push(Spans::from(Span::styled(section.title, app.theme.heading)));
+
+        let width = section
+            .rows
+            .iter()
+            .map(|(keys, _)| keys.chars().count())
+            .max()
+            .unwrap_or(0);
+        for (keys, description) in section.rows {
+            let padding = width - keys.chars().count();
+            text.push(Spans::from(vec![
+                Span::raw("  "),
+                Span::styled(keys, app.theme.key),
+                Span::raw(" ".repeat(padding + 2)),
+                Span::raw(description),
+            ]));
+        }
+    }
+
+    app.help.viewport = area.height.saturating_sub(2) as usize;
+    app.help.len = text.len();
+    app.help.scroll_by(0);
+
+    let help = Paragraph::new(text)
+        .block(
+            Block::default()
+                .title(messages().help)
+                .borders(Borders::ALL)
+                .border_type(tui::widgets::BorderType::Rounded)
+                .border_style(app.theme.focus),
+        )
+        .scroll((app.help.scroll as u16, 0));
+
+    f.render_widget(Clear, area);
+    f.render_widget(help, area);
+}
+
+/// Vertical scrollbar in the last column of `area`, hidden when everything fits
+fn render_scrollbar<B: Backend>(
+    f: &mut Frame<B>,
+    theme: &Theme,
+    area: Rect,
+    scroll: usize,
+    total: usize,
+) {
+    let height = area.height as usize;
+    if area.width == 0 || total <= height {
+        return;
+    }
+
+    let thumb = (height * height / total).max(1);
+    let start = scroll * (height - thumb) / (total - height);
+
+    let bar: Vec<Spans> = (0..height)
+        .map(|i| {
+            if (start..start + thumb).contains(&i) {
+                Spans::from(Span::styled("█", theme.scrollbar))
+            } else {
+                Spans::from(Span::styled("│", theme.muted))
+            }
+        })
+        .collect();
+
+    let column = Rect::new(area.right() - 1, area.y, 1, area.height);
+    f.render_widget(Paragraph::new(bar), column);
+}
+
+/// Article with its position in the title, the scrollbar and highlights
+fn render_article<B: Backend>(
+    f: &mut Frame<B>,
+    article: &mut Article,
+    theme: &Theme,
+    area: Rect,
+    block: Block,
+) {
+    // One column on the right is taken by the scrollbar
+    let inner = block.inner(area);
+    let width = inner.width.saturating_sub(1) as usize;
+    article.viewport = inner.height.max(1) as usize;
+
+    // The content may have become shorter after a resize
+    article.lines(width);
+    article.scroll_by(0);
+
+    let (line, total, read) = article.position();
+    let m = messages();
+    let mut title = fill(m.article_position, &[&article.title, &line, &total, &read]);
+    if let Some((current, count)) = article.match_position() {
+        title.push_str(&fill(m.match_position, &[&current, &count]));
+    }
+    let block = block.title(title);
+
+    let selected = article.selected_link;
+    let (scroll, columns) = article.scroll;
+    let scroll = scroll as usize;
+    let visible = article
+        .lines(width)
+        .len()
+        .min(scroll + inner.height as usize);
+    let text: Vec<Spans> = (scroll..visible)
+        .map(|y| {
+            Spans::from(
+                article
+                    .marked(y)
+                    .into_iter()
+                    .map(|(text, link, mark)| {
+                        let style = match link {
+                            Some(l) if Some(l) == selected => theme.selected_link,
+                            Some(_) => theme.link,
+                            None => theme.text,
+                        };
+                        let style = match mark {
+                            Mark::CurrentMatch => style.patch(theme.current_found),
+                            Mark::Match => style.patch(theme.found),
+                            Mark::None => style,
+                        };
+                        Span::styled(text, style)
+                    })
+                    .collect::<Vec<Span>>(),
+            )
+        })
+        .collect();
+
+    // Lines are already wrapped, so the paragraph only draws them
+    let explorer = Paragraph::new(text).block(block).scroll((0, columns));
+    // Render block for explore objects
+    f.render_widget(explorer, area);
+    render_scrollbar(f, theme, inner, scroll, total);
 }
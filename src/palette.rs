@@ -0,0 +1,136 @@
+use crate::{
+    keymap::{Action, Keymap},
+    stateful::StatefulList,
+};
+
+/**
+Scores how well `pattern` matches `text`: every char of the pattern has to
+appear in the text in the same order. Consecutive chars and matches at the
+start of words score higher. `None` means no match.
+*/
+pub fn fuzzy_score(pattern: &str, text: &str) -> Option<i64> {
+    let text: Vec<char> = text.to_lowercase().chars().collect();
+    let mut score = 0;
+    let mut pos = 0;
+    let mut last: Option<usize> = None;
+
+    for p in pattern
+        .to_lowercase()
+        .chars()
+        .filter(|c| !c.is_whitespace())
+    {
+        let found = text[pos..].iter().position(|c| *c == p)? + pos;
+
+        score += 1;
+        if last.is_some_and(|l| l + 1 == found) {
+            score += 5;
+        }
+        if found == 0 || !text[found - 1].is_alphanumeric() {
+            score += 3;
+        }
+
+        last = Some(found);
+        pos = found + 1;
+    }
+
+    Some(score)
+}
+
+/// Overlay listing every action with its keys, filtered by a fuzzy query
+#[derive(Clone)]
+pub struct Palette {
+    pub input: String,
+    pub items: StatefulList<Action>,
+}
+
+impl Default for Palette {
+    fn default() -> Self {
+        let mut palette = Palette {
+            input: String::new(),
+            items: StatefulList::with_items(Action::ALL.to_vec()),
+        };
+        palette.items.select_first();
+
+        palette
+    }
+}
+
+impl Palette {
+    /// Text the query is matched against: the description and the keys
+    pub fn label(action: Action, keymap: &Keymap) -> String {
+        let keys: Vec<String> = keymap.keys(action).iter().map(|k| k.to_string()).collect();
+
+        format!("{} {}", action.description(), keys.join(" "))
+    }
+
+    /// Applies the query, the best matches go first
+    pub fn filter(&mut self, keymap: &Keymap) {
+        let mut scored: Vec<(usize, i64)> = self
+            .items
+            .all()
+            .iter()
+            .enumerate()
+            .filter_map(|(i, a)| Some((i, fuzzy_score(&self.input, &Palette::label(*a, keymap))?)))
+            .collect();
+
+        // Stable, so equal scores keep the order of `Action::ALL`
+        scored.sort_by_key(|(_, score)| -score);
+
+        self.items
+            .set_view(scored.into_iter().map(|(i, _)| i).collect());
+        self.items.select_first();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn empty_pattern_matches_anything() {
+        assert_eq!(fuzzy_score("", "Open"), Some(0));
+        assert_eq!(fuzzy_score(" ", ""), Some(0));
+    }
+
+    #[test]
+    fn chars_must_keep_their_order() {
+        assert!(fuzzy_score("opn", "Open").is_some());
+        assert_eq!(fuzzy_score("npo", "Open"), None);
+        assert_eq!(fuzzy_score("openn", "Open"), None);
+        assert_eq!(fuzzy_score("x", ""), None);
+    }
+
+    #[test]
+    fn ignores_case_and_spaces() {
+        assert_eq!(fuzzy_score("OPEN", "open"), fuzzy_score("open", "OPEN"));
+        assert_eq!(
+            fuzzy_score("next tab", "Next tab"),
+            fuzzy_score("nexttab", "Next tab")
+        );
+        assert!(fuzzy_score("тем", "Сменить ТЕМУ").is_some());
+    }
+
+    #[test]
+    fn consecutive_chars_score_higher() {
+        assert!(fuzzy_score("ab", "xaby") > fuzzy_score("ab", "xaxb"));
+    }
+
+    #[test]
+    fn word_starts_score_higher() {
+        assert!(fuzzy_score("s", "Sort") > fuzzy_score("s", "Close"));
+        assert!(fuzzy_score("nt", "Next tab") > fuzzy_score("nt", "Find next"));
+    }
+
+    #[test]
+    fn filter_puts_the_best_match_first() {
+        let keymap = Keymap::default();
+        // Descriptions depend on the locale, so the query is taken from one
+        let mut palette = Palette {
+            input: Action::ToggleZen.description().to_string(),
+            ..Palette::default()
+        };
+        palette.filter(&keymap);
+
+        assert_eq!(palette.items.selected(), Some(&Action::ToggleZen));
+    }
+}
@@ -1,19 +1,88 @@
 use core::fmt;
-
-use reqwest::StatusCode;
+use std::{
+    collections::HashMap,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, OnceLock,
+    },
+    time::Duration,
+};
+
+use reqwest::{Client, Response, StatusCode};
 use scraper::Selector;
 use serde::{Deserialize, Serialize};
-
-use crate::caching::{cache_objects, decache_objects};
+use tokio::{
+    sync::Semaphore,
+    task::JoinSet,
+    time::{interval, MissedTickBehavior},
+};
+
+use crate::{
+    locale,
+    store::{
+        article_entries, cache_article, cache_objects, decache_article, decache_objects,
+        objects_age,
+    },
+};
 
 /**
  **One value must be greater than**
  */
 const MAX_SERIES: u8 = 9;
-const URL_SERIES: &str = "https://scpfoundation.net/scp-series";
-const URL_SCP_OBJECT_PAGE: &str = "https://scpfoundation.net/api/articles/scp-";
+const DEFAULT_BASE_URL: &str = "https://scpfoundation.net";
+const DEFAULT_BRANCH: &str = "scp-series";
+/// Branches of the site switched between from the palette, see [`Site::branch`]
+pub const BRANCHES: [&str; 2] = [DEFAULT_BRANCH, "scp-series-ru"];
+
+/// Set when the site couldn't be reached the last time, cleared by any answer
+static OFFLINE: AtomicBool = AtomicBool::new(false);
+/// Shared by every request, so connections to the site are reused
+static CLIENT: OnceLock<Client> = OnceLock::new();
+
+/// Site the objects and the articles are scraped from
+#[derive(Debug, Clone)]
+pub struct Site {
+    pub base_url: String,
+    /// Page of the first series, the next ones get `-2`, `-3` and so on
+    pub branch: String,
+    /// How many series pages are scraped at once
+    pub concurrency: usize,
+    /// Pause between two articles loaded by [`mirror`]
+    pub mirror_delay: Duration,
+}
+
+impl Default for Site {
+    fn default() -> Self {
+        Site {
+            base_url: String::from(DEFAULT_BASE_URL),
+            branch: String::from(DEFAULT_BRANCH),
+            concurrency: 4,
+            mirror_delay: Duration::from_millis(500),
+        }
+    }
+}
+
+impl Site {
+    fn series_url(&self, series: u8) -> String {
+        let base = self.base_url.trim_end_matches('/');
+
+        if series == 1 {
+            format!("{}/{}", base, self.branch)
+        } else {
+            format!("{}/{}-{}", base, self.branch, series)
+        }
+    }
 
-#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+    fn article_url(&self, page_id: &str) -> String {
+        format!(
+            "{}/api/articles/{}",
+            self.base_url.trim_end_matches('/'),
+            page_id
+        )
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
 pub enum ClassificationScp {
     None,
     Safe,
@@ -24,20 +93,41 @@ pub enum ClassificationScp {
     NonStandard,
 }
 
-impl fmt::Display for ClassificationScp {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+impl ClassificationScp {
+    /// Accepts both the english class name and the russian one
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "safe" | "безопасный" => Some(ClassificationScp::Safe),
+            "euclid" | "евклид" => Some(ClassificationScp::Euclid),
+            "keter" | "кетер" => Some(ClassificationScp::Keter),
+            "thaumiel" | "таумиэль" => Some(ClassificationScp::Thaumiel),
+            "neutralized" | "нейтрализован" => Some(ClassificationScp::Neutralized),
+            "nonstandard" | "нестандартный" => Some(ClassificationScp::NonStandard),
+            "none" | "отсутствует" => Some(ClassificationScp::None),
+            _ => None,
+        }
+    }
+
+    /// English name understood by [`ClassificationScp::from_name`], as kept in the store
+    pub fn key(&self) -> &'static str {
         match self {
-            ClassificationScp::Euclid => write!(f, "Евклид"),
-            ClassificationScp::Keter => write!(f, "Кетер"),
-            ClassificationScp::Neutralized => write!(f, "Нейтрализован"),
-            ClassificationScp::None => write!(f, "Отсутствует"),
-            ClassificationScp::Safe => write!(f, "Безопасный"),
-            ClassificationScp::Thaumiel => write!(f, "Таумиэль"),
-            ClassificationScp::NonStandard => write!(f, "Нестандартный класс"),
+            ClassificationScp::None => "none",
+            ClassificationScp::Safe => "safe",
+            ClassificationScp::Euclid => "euclid",
+            ClassificationScp::Keter => "keter",
+            ClassificationScp::Thaumiel => "thaumiel",
+            ClassificationScp::Neutralized => "neutralized",
+            ClassificationScp::NonStandard => "nonstandard",
         }
     }
 }
 
+impl fmt::Display for ClassificationScp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", locale::class_name(self))
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ScpObject {
     class: ClassificationScp,
@@ -46,17 +136,16 @@ pub struct ScpObject {
 }
 
 impl ScpObject {
-    fn new(class: ClassificationScp, name: String, id: String) -> Self {
+    pub fn new(class: ClassificationScp, name: String, id: String) -> Self {
         ScpObject { class, id, name }
     }
 
     pub fn get_document_name(&self) -> String {
-        String::from(format!("SCP-{}", self.id))
+        format!("SCP-{}", self.id)
     }
 
     pub fn get_name(&self) -> String {
-        let r = format!("{}", &self.name);
-        r.clone()
+        self.name.clone()
     }
 
     pub fn get_class(&self) -> &ClassificationScp {
@@ -64,40 +153,122 @@ impl ScpObject {
     }
 
     pub fn get_id(&self) -> String {
-        let r = format!("{}", &self.id);
-        r.clone()
+        self.id.clone()
+    }
+
+    /// Whether the object has this id in any case, `Jump` lowercases it while scraped ids are uppercase
+    pub fn has_id(&self, id: &str) -> bool {
+        self.id.eq_ignore_ascii_case(id)
+    }
+
+    /// Id of the article in the API, e.g. `scp-002` or `scp-ru-1234`
+    pub fn get_page_id(&self) -> String {
+        format!("scp-{}", self.id.to_lowercase())
+    }
+
+    /// Series are by a thousand objects: 001-999 is the first, 1000-1999 the second
+    pub fn get_series(&self) -> Option<u32> {
+        self.id.parse::<u32>().ok().map(|n| n / 1000 + 1)
     }
 }
 
-pub async fn parse_all() -> Vec<ScpObject> {
-    let mut objects: Vec<ScpObject> = Vec::new();
+/// Objects for the list and whether they are as fresh as they should be
+#[derive(Debug, Default)]
+pub struct Catalog {
+    pub objects: Vec<ScpObject>,
+    /// Scraping failed, the objects are the cached ones or there are none at all
+    pub stale: bool,
+}
+
+/**
+Cached objects while they are younger than `ttl`, zero keeps them forever.
+An expired cache is still used when some series page can't be loaded.
+*/
+pub async fn parse_all(site: &Site, ttl: Duration) -> Catalog {
+    let expired = !ttl.is_zero() && objects_age().is_none_or(|age| age > ttl);
 
     match decache_objects() {
-        Ok(o) => o,
-        Err(_) => {
-            objects.append(&mut parse_series(URL_SERIES).await);
+        Ok(objects) if !expired => Catalog {
+            objects,
+            stale: false,
+        },
+        cached => match refresh_all(site).await {
+            Some(objects) => Catalog {
+                objects,
+                stale: false,
+            },
+            None => Catalog {
+                objects: cached.unwrap_or_default(),
+                stale: true,
+            },
+        },
+    }
+}
 
-            for i in 2..MAX_SERIES {
-                objects.append(&mut parse_series(format!("{}-{}", URL_SERIES, i).as_str()).await);
-            }
+/**
+Scrapes every series page ignoring the cache and caches the result. `None`
+when any page failed, a part of the objects would replace the whole cache.
+*/
+pub async fn refresh_all(site: &Site) -> Option<Vec<ScpObject>> {
+    let objects = scrape_all(site).await?;
 
-            cache_objects(objects.clone());
+    // Without a cache the objects are scraped again on the next start
+    let _ = cache_objects(&objects);
+
+    Some(objects)
+}
 
-            objects
+/// Scrapes every series page without touching the cache, see [`refresh_all`]
+pub async fn scrape_all(site: &Site) -> Option<Vec<ScpObject>> {
+    let permits = Arc::new(Semaphore::new(site.concurrency.max(1)));
+    let mut pages = JoinSet::new();
+
+    for series in 1..MAX_SERIES {
+        let url = site.series_url(series);
+        let permits = permits.clone();
+        pages.spawn(async move {
+            let _permit = permits.acquire_owned().await;
+            (series, parse_series(&url).await)
+        });
+    }
+
+    // Pages finish in any order, the objects are kept in the order of series
+    let mut series: Vec<(u8, Vec<ScpObject>)> = Vec::new();
+    while let Some(page) = pages.join_next().await {
+        match page {
+            Ok((n, Some(objects))) => series.push((n, objects)),
+            _ => return None,
         }
     }
+    series.sort_by_key(|(n, _)| *n);
+
+    let objects: Vec<ScpObject> = series.into_iter().flat_map(|(_, o)| o).collect();
+    if objects.is_empty() {
+        return None;
+    }
+
+    Some(objects)
 }
 
-pub async fn parse_series(url: &str) -> Vec<ScpObject> {
-    let mut objects: Vec<ScpObject> = Vec::new();
+/// Objects of one series page, `None` when the page can't be loaded
+pub async fn parse_series(url: &str) -> Option<Vec<ScpObject>> {
+    let response = get(url).await.ok()?;
+    if response.status() != StatusCode::OK {
+        return None;
+    }
+    let response = response.text().await.ok()?;
+
+    Some(parse_series_page(&response))
+}
 
-    let response = reqwest::get(url).await.unwrap().text().await.unwrap();
+/// Objects listed in the html of a series page
+fn parse_series_page(html: &str) -> Vec<ScpObject> {
+    let mut objects: Vec<ScpObject> = Vec::new();
 
-    // let response = fs::read_to_string(URL_SERIES).expect("Should have been able to read the file");
-    let document = scraper::Html::parse_document(&response);
+    let document = scraper::Html::parse_document(html);
 
     let paragraph_selector: Selector = scraper::Selector::parse("#page-content>p").unwrap();
-    let paragraphs = document.select(&paragraph_selector).map(|x| x);
+    let paragraphs = document.select(&paragraph_selector);
 
     paragraphs.zip(1..101).for_each(|(i, _)| {
         let childrens: Vec<_> = i
@@ -114,14 +285,13 @@ pub async fn parse_series(url: &str) -> Vec<ScpObject> {
                         .unwrap()
                         .starts_with("SCP")
             })
-            .map(|e| e)
             .collect();
 
         childrens.iter().for_each(|c| {
-            let name_d = c.first_child().unwrap().value().as_text().unwrap();
-            let name_d: Vec<_> = name_d.split("-").collect();
+            let name_d = c.first_child().unwrap().value().as_text().unwrap().trim();
 
-            let id = name_d.get(1).unwrap().trim();
+            // Everything after the prefix, branches keep theirs: `SCP-RU-1234` is `RU-1234`
+            let id = name_d.strip_prefix("SCP-").unwrap_or(name_d).trim();
 
             if c.next_sibling().unwrap().value().is_text()
                 || c.next_sibling().unwrap().value().is_element()
@@ -137,17 +307,18 @@ pub async fn parse_series(url: &str) -> Vec<ScpObject> {
 
                 let mut name: Option<&str> = None;
 
-                if !span.is_none() && span.unwrap().value().is_element() {
-                    let elm = span.unwrap().value().as_element().unwrap();
+                if let Some(span) = span.filter(|s| s.value().is_element()) {
+                    let elm = span.value().as_element().unwrap();
                     if elm.name() == "span" {
-                        let text_from_span = span.unwrap().children().next();
-                        if !text_from_span.is_none() && text_from_span.unwrap().value().is_text() {
-                            name = Some(text_from_span.unwrap().value().as_text().unwrap().trim());
+                        if let Some(text_from_span) =
+                            span.children().next().filter(|t| t.value().is_text())
+                        {
+                            name = Some(text_from_span.value().as_text().unwrap().trim());
                         }
                     }
                 }
 
-                if name == None {
+                if name.is_none() {
                     name = Some(
                         c.next_sibling()
                             .unwrap()
@@ -249,7 +420,7 @@ pub async fn parse_series(url: &str) -> Vec<ScpObject> {
 
 **/
 #[allow(unused)]
-#[derive(Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug)]
 pub struct ApiObjectResult {
     #[serde(rename(deserialize = "pageId"))]
     pub page_id: String,
@@ -260,23 +431,159 @@ pub struct ApiObjectResult {
     pub source: String,
     pub tags: Vec<String>,
     pub locked: bool,
+    /// Not every response carries the rating of the page
+    #[serde(default)]
+    pub rating: Option<i32>,
 }
 
 pub async fn debug() {}
 
-pub async fn parse_object_page(id: &str) -> Option<ApiObjectResult> {
-    let path = format!("{}{}", URL_SCP_OBJECT_PAGE, id);
+/// Whether the last request couldn't reach the site, cached pages are shown meanwhile
+pub fn is_offline() -> bool {
+    OFFLINE.load(Ordering::Relaxed)
+}
 
-    let response = reqwest::get(path).await;
+/// Every request to the site goes here to keep [`is_offline`] up to date
+async fn get(url: &str) -> reqwest::Result<Response> {
+    let response = CLIENT.get_or_init(Client::new).get(url).send().await;
+    OFFLINE.store(response.is_err(), Ordering::Relaxed);
 
-    match response {
-        Ok(r) => {
-            if r.status() == StatusCode::OK {
-                return Some(serde_json::from_str(&r.text().await.unwrap()).unwrap());
-            }
+    response
+}
+
+pub async fn parse_object_page(site: &Site, id: &str, ttl: Duration) -> Option<ApiObjectResult> {
+    parse_page(site, &format!("scp-{}", id.to_lowercase()), ttl).await
+}
+
+/**
+Any page of the site by its id, e.g. `scp-002` or `euclid`. Cached pages
+are used while they are younger than `ttl`, zero keeps them forever. An
+expired page is still shown when the site can't be reached.
+*/
+pub async fn parse_page(site: &Site, page_id: &str, ttl: Duration) -> Option<ApiObjectResult> {
+    let cached = decache_article(page_id).ok();
+
+    match cached {
+        Some((article, age)) if ttl.is_zero() || age <= ttl => Some(article),
+        Some((article, _)) => Some(fetch_page(site, page_id).await.unwrap_or(article)),
+        None => fetch_page(site, page_id).await,
+    }
+}
 
-            None
+/// Loads the page from the site ignoring the cache and caches it
+pub async fn fetch_page(site: &Site, page_id: &str) -> Option<ApiObjectResult> {
+    let response = get(&site.article_url(page_id)).await.ok()?;
+    if response.status() != StatusCode::OK {
+        return None;
+    }
+
+    let article: ApiObjectResult = serde_json::from_str(&response.text().await.ok()?).ok()?;
+    // The page is loaded again next time when it can't be cached
+    let _ = cache_article(page_id, &article);
+
+    Some(article)
+}
+
+/// What [`mirror`] did with the articles of the objects
+#[derive(Debug, Default)]
+pub struct MirrorReport {
+    pub loaded: usize,
+    /// Already cached and younger than the ttl
+    pub skipped: usize,
+    /// Page ids which couldn't be loaded
+    pub failed: Vec<String>,
+}
+
+/**
+Loads the article of every object into the store, one per `site.mirror_delay`
+so the site isn't flooded. Articles younger than `ttl` are skipped, which is
+how an interrupted mirror resumes: every article is stored as soon as it is
+loaded. `progress` gets the number of the object, their count and the page.
+*/
+pub async fn mirror(
+    site: &Site,
+    objects: &[ScpObject],
+    ttl: Duration,
+    mut progress: impl FnMut(usize, usize, &str),
+) -> MirrorReport {
+    let ages: HashMap<String, Duration> = article_entries()
+        .unwrap_or_default()
+        .into_iter()
+        .map(|e| (e.page_id, e.age))
+        .collect();
+
+    let mut report = MirrorReport::default();
+    let mut ticks = interval(site.mirror_delay.max(Duration::from_millis(1)));
+    ticks.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+    for (i, object) in objects.iter().enumerate() {
+        let page_id = object.get_page_id();
+        if ages
+            .get(&page_id)
+            .is_some_and(|age| ttl.is_zero() || *age <= ttl)
+        {
+            report.skipped += 1;
+            continue;
         }
-        Err(_) => None,
+
+        ticks.tick().await;
+        progress(i + 1, objects.len(), &page_id);
+
+        match fetch_page(site, &page_id).await {
+            Some(_) => report.loaded += 1,
+            None => report.failed.push(page_id),
+        }
+    }
+
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SERIES: &str = r#"<html><body><div id="page-content">
+<p><img alt="euclid.png" src="euclid.png"> <a href="/scp-002">SCP-002</a> — «Живая» комната<br>
+<img alt="keter.png" src="keter.png"> <a href="/scp-ru-1234">SCP-RU-1234</a> — Объект филиала</p>
+</div></body></html>"#;
+
+    #[test]
+    fn series_page_keeps_the_branch_in_ids() {
+        let objects = parse_series_page(SERIES);
+
+        assert_eq!(objects.len(), 2);
+        assert_eq!(objects[0].get_id(), "002");
+        assert_eq!(objects[0].get_name(), "«Живая» комната");
+        assert_eq!(*objects[0].get_class(), ClassificationScp::Euclid);
+        assert_eq!(objects[1].get_id(), "RU-1234");
+        assert_eq!(objects[1].get_name(), "Объект филиала");
+        assert_eq!(*objects[1].get_class(), ClassificationScp::Keter);
+    }
+
+    #[test]
+    fn page_ids_are_lowercase() {
+        let object = ScpObject::new(
+            ClassificationScp::Keter,
+            String::from("Объект филиала"),
+            String::from("RU-1234"),
+        );
+
+        assert_eq!(object.get_page_id(), "scp-ru-1234");
+        assert_eq!(object.get_document_name(), "SCP-RU-1234");
+        assert_eq!(object.get_series(), None);
+    }
+
+    #[test]
+    fn ids_match_in_any_case() {
+        let object = ScpObject::new(
+            ClassificationScp::Keter,
+            String::from("Объект филиала"),
+            String::from("RU-1234"),
+        );
+
+        assert!(object.has_id("ru-1234"));
+        assert!(object.has_id("RU-1234"));
+        assert!(!object.has_id("ru-123"));
+        assert!(!object.has_id("1234"));
     }
 }
@@ -0,0 +1,81 @@
+use std::{
+    path::{Path, PathBuf},
+    sync::OnceLock,
+};
+
+use crate::cli::Cli;
+
+const APP_DIR: &str = "scpfoundation-explorer";
+const CONFIG_FILE: &str = "config.toml";
+
+/// Where the config, the user data and the caches live
+#[derive(Debug, Clone)]
+pub struct Paths {
+    pub config: PathBuf,
+    /// Saved searches and tabs
+    pub data: PathBuf,
+    /// Everything that can be scraped again
+    pub cache: PathBuf,
+}
+
+static PATHS: OnceLock<Paths> = OnceLock::new();
+
+impl Paths {
+    /**
+    XDG locations unless they are given on the command line. Platforms
+    without them, or a missing home, fall back to the current directory.
+    */
+    pub fn resolve(cli: &Cli) -> Paths {
+        let app_dir = |base: Option<PathBuf>| {
+            base.map(|b| b.join(APP_DIR))
+                .unwrap_or_else(|| PathBuf::from("."))
+        };
+
+        Paths {
+            config: cli
+                .config
+                .clone()
+                .unwrap_or_else(|| app_dir(dirs::config_dir()).join(CONFIG_FILE)),
+            data: cli
+                .data_dir
+                .clone()
+                .unwrap_or_else(|| app_dir(dirs::data_dir())),
+            cache: cli
+                .cache_dir
+                .clone()
+                .unwrap_or_else(|| app_dir(dirs::cache_dir())),
+        }
+    }
+}
+
+/// Sets the paths of the whole app, only the first call has an effect
+pub fn init(paths: Paths) {
+    let _ = PATHS.set(paths);
+}
+
+fn get() -> &'static Paths {
+    PATHS.get_or_init(|| Paths::resolve(&Cli::default()))
+}
+
+pub fn config_file() -> &'static Path {
+    &get().config
+}
+
+/// File in the data directory, the directory is created when missing
+pub fn data_file(name: &str) -> PathBuf {
+    let _ = std::fs::create_dir_all(&get().data);
+    get().data.join(name)
+}
+
+/// Directory inside the cache directory, created when missing
+pub fn cache_dir(name: &str) -> PathBuf {
+    let dir = get().cache.join(name);
+    let _ = std::fs::create_dir_all(&dir);
+    dir
+}
+
+/// File in the cache directory, the directory is created when missing
+pub fn cache_file(name: &str) -> PathBuf {
+    let _ = std::fs::create_dir_all(&get().cache);
+    get().cache.join(name)
+}
@@ -0,0 +1,138 @@
+use serde::{Deserialize, Serialize};
+
+use crate::parsing::{ClassificationScp, ScpObject};
+
+/**
+Search query typed into the "Поиск" block.
+
+Words are matched against the document name and the title of an object,
+all of them must be found. Besides words a query may contain filters:
+
+- `class:keter` or `класс:кетер`
+- `series:3` or `серия:3`
+
+## Example
+
+`class:keter series:3 кукла`
+*/
+#[derive(Debug, Default, PartialEq)]
+pub struct Query {
+    words: Vec<String>,
+    classes: Vec<ClassificationScp>,
+    series: Vec<u32>,
+}
+
+impl Query {
+    pub fn parse(s: &str) -> Self {
+        let mut query = Query::default();
+
+        for word in s.split_whitespace() {
+            let word = word.to_lowercase();
+
+            match word.split_once(':') {
+                Some(("class" | "класс", class)) => {
+                    match ClassificationScp::from_name(class) {
+                        Some(c) => query.classes.push(c),
+                        None => query.words.push(word),
+                    }
+                }
+                Some(("series" | "серия", series)) => match series.parse::<u32>() {
+                    Ok(n) => query.series.push(n),
+                    Err(_) => query.words.push(word),
+                },
+                _ => query.words.push(word),
+            }
+        }
+
+        query
+    }
+
+    pub fn matches(&self, object: &ScpObject) -> bool {
+        if !self.classes.is_empty() && !self.classes.contains(object.get_class()) {
+            return false;
+        }
+
+        if !self.series.is_empty()
+            && !object
+                .get_series()
+                .is_some_and(|s| self.series.contains(&s))
+        {
+            return false;
+        }
+
+        let document_name = object.get_document_name().to_lowercase();
+        let name = object.get_name().to_lowercase();
+
+        self.words
+            .iter()
+            .all(|w| document_name.contains(w) || name.contains(w))
+    }
+}
+
+/**
+Query saved by the user under a name, it is kept as text and parsed again
+every time, so the list always reflects the current catalog
+*/
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SavedSearch {
+    pub name: String,
+    pub query: String,
+}
+
+impl SavedSearch {
+    pub fn new(name: String, query: String) -> Self {
+        SavedSearch { name, query }
+    }
+
+    pub fn count(&self, objects: &[ScpObject]) -> usize {
+        let query = Query::parse(&self.query);
+        objects.iter().filter(|o| query.matches(o)).count()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn object(class: ClassificationScp, name: &str, id: &str) -> ScpObject {
+        ScpObject::new(class, name.to_string(), id.to_string())
+    }
+
+    #[test]
+    fn parses_filters_mixed_with_words() {
+        let query = Query::parse("Кукла class:keter  series:3 старая КЛАСС:Евклид");
+
+        assert_eq!(
+            query,
+            Query {
+                words: vec!["кукла".to_string(), "старая".to_string()],
+                classes: vec![ClassificationScp::Keter, ClassificationScp::Euclid],
+                series: vec![3],
+            }
+        );
+    }
+
+    #[test]
+    fn unknown_filters_are_words() {
+        let query = Query::parse("class:purple серия:три note:1");
+
+        assert_eq!(query.words, ["class:purple", "серия:три", "note:1"]);
+        assert!(query.classes.is_empty());
+        assert!(query.series.is_empty());
+    }
+
+    #[test]
+    fn matches_every_part_of_the_query() {
+        let query = Query::parse("class:keter series:3 кукла");
+
+        assert!(query.matches(&object(ClassificationScp::Keter, "Старая кукла", "2316")));
+        assert!(!query.matches(&object(ClassificationScp::Euclid, "Старая кукла", "2316")));
+        assert!(!query.matches(&object(ClassificationScp::Keter, "Старая кукла", "173")));
+        assert!(!query.matches(&object(ClassificationScp::Keter, "Статуя", "2316")));
+        assert!(Query::parse("scp-2316").matches(&object(
+            ClassificationScp::Keter,
+            "Статуя",
+            "2316"
+        )));
+    }
+}
@@ -1,80 +1,430 @@
+use std::sync::Arc;
+
 use tui::widgets::ListState;
 
+/**
+List with a selection which shows a view over a shared backing store.
+
+The view holds indices into `items`, so filtering and sorting never copy
+the items themselves and clones of the list share the same store.
+*/
 #[derive(Clone)]
 pub struct StatefulList<T> {
     pub state: ListState,
-    pub items: Vec<T>,
-    selected: usize,
+    items: Arc<Vec<T>>,
+    view: Vec<usize>,
+    /// Rows the list occupies on screen, used for page moves
+    viewport: usize,
+    /// First visible row, mirrors the private offset of `ListState`
+    offset: usize,
+    /// Whether `next` on the last item goes to the first one and vice versa
+    wrap: bool,
+}
+
+impl<T> Default for StatefulList<T> {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl<T> StatefulList<T> {
     pub fn with_items(items: Vec<T>) -> StatefulList<T> {
+        StatefulList::with_shared(Arc::new(items))
+    }
+
+    pub fn with_shared(items: Arc<Vec<T>>) -> StatefulList<T> {
         StatefulList {
             state: ListState::default(),
+            view: (0..items.len()).collect(),
             items,
-            selected: 0,
+            viewport: 1,
+            offset: 0,
+            wrap: true,
         }
     }
 
     pub fn new() -> StatefulList<T> {
-        StatefulList {
-            state: ListState::default(),
-            items: Vec::new(),
-            selected: 0,
+        StatefulList::with_items(Vec::new())
+    }
+
+    pub fn set_wrap(&mut self, wrap: bool) {
+        self.wrap = wrap;
+    }
+
+    /// Called on every render with the number of rows visible at once
+    pub fn set_viewport(&mut self, height: usize) {
+        self.viewport = height.max(1);
+
+        // Same scrolling as the `List` widget does for single-row items
+        match self.state.selected() {
+            Some(s) if s >= self.offset + self.viewport => self.offset = s + 1 - self.viewport,
+            Some(s) if s < self.offset => self.offset = s,
+            Some(_) => {}
+            None => self.offset = 0,
         }
+        self.offset = self.offset.min(self.view.len().saturating_sub(1));
     }
 
-    pub fn select_first(&mut self) {
-        if self.items.len() > 0 {
-            self.selected = 0;
-            self.state.select(Some(0));
+    /// Position in the view of the item shown at `row` of the list area
+    pub fn item_at_row(&self, row: usize) -> Option<usize> {
+        let i = self.offset + row;
+        (row < self.viewport && i < self.view.len()).then_some(i)
+    }
+
+    /// Replaces the backing store, the view is reset to every item
+    pub fn set_items(&mut self, items: Arc<Vec<T>>) {
+        self.view = (0..items.len()).collect();
+        self.items = items;
+        self.unselect();
+    }
+
+    /**
+    Shows only the items at `view`, in that order. The selected item stays
+    selected if it is still visible, otherwise the selection is dropped.
+    */
+    pub fn set_view(&mut self, view: Vec<usize>) {
+        let selected = self
+            .state
+            .selected()
+            .and_then(|i| self.view.get(i).copied());
+        self.view = view;
+
+        let i = selected.and_then(|s| self.view.iter().position(|i| *i == s));
+        self.state.select(i);
+    }
+
+    /// Every item of the backing store, visible or not
+    pub fn all(&self) -> &[T] {
+        &self.items
+    }
+
+    /// Visible items in the order of the view
+    pub fn iter(&self) -> impl Iterator<Item = &T> {
+        self.view.iter().map(|i| &self.items[*i])
+    }
+
+    /// Visible item at position `i` of the view
+    pub fn get(&self, i: usize) -> Option<&T> {
+        self.view.get(i).map(|i| &self.items[*i])
+    }
+
+    pub fn len(&self) -> usize {
+        self.view.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.view.is_empty()
+    }
+
+    pub fn selected(&self) -> Option<&T> {
+        self.state.selected().and_then(|i| self.get(i))
+    }
+
+    /// Position of the selected item in the view
+    pub fn selected_index(&self) -> Option<usize> {
+        self.state.selected()
+    }
+
+    /// Selects position `i` of the view, clamped to the last item
+    pub fn select(&mut self, i: usize) {
+        if self.view.is_empty() {
+            self.unselect();
+        } else {
+            self.state.select(Some(i.min(self.view.len() - 1)));
         }
     }
 
+    pub fn select_first(&mut self) {
+        self.select(0);
+    }
+
     pub fn select_last(&mut self) {
-        if self.items.len() > 0 {
-            self.selected = self.items.len() - 1;
-            self.state.select(Some(self.items.len() - 1));
-        }
+        self.select(usize::MAX);
     }
 
-    pub fn next(&mut self) {
+    /// Moves the selection by `delta` items, nothing is selected yet means
+    /// the first item. Only single steps wrap around, longer moves stop at
+    /// the ends of the list.
+    fn move_by(&mut self, delta: isize) {
+        let len = self.view.len();
+        if len == 0 {
+            self.unselect();
+            return;
+        }
+
         let i = match self.state.selected() {
-            Some(i) => {
-                if i >= self.items.len() - 1 {
-                    0
-                } else {
-                    i + 1
-                }
-            }
             None => 0,
+            Some(i) if self.wrap && delta == 1 && i + 1 >= len => 0,
+            Some(0) if self.wrap && delta == -1 => len - 1,
+            Some(i) if delta < 0 => i.saturating_sub(delta.unsigned_abs()),
+            Some(i) => i.saturating_add(delta as usize).min(len - 1),
         };
 
-        self.selected = i;
         self.state.select(Some(i));
     }
 
+    pub fn next(&mut self) {
+        self.move_by(1);
+    }
+
     pub fn previous(&mut self) {
-        let i = match self.state.selected() {
+        self.move_by(-1);
+    }
+
+    pub fn page_down(&mut self) {
+        self.move_by(self.viewport as isize);
+    }
+
+    pub fn page_up(&mut self) {
+        self.move_by(-(self.viewport as isize));
+    }
+
+    pub fn half_page_down(&mut self) {
+        self.move_by((self.viewport / 2).max(1) as isize);
+    }
+
+    pub fn half_page_up(&mut self) {
+        self.move_by(-((self.viewport / 2).max(1) as isize));
+    }
+
+    /// Selects the first visible item matching `predicate`
+    pub fn select_where<P: Fn(&T) -> bool>(&mut self, predicate: P) -> bool {
+        let found = self.iter().position(predicate);
+
+        match found {
             Some(i) => {
-                if i == 0 {
-                    self.items.len() - 1
-                } else {
-                    i - 1
-                }
+                self.state.select(Some(i));
+                true
             }
-            None => 0,
-        };
-
-        self.selected = i;
-        self.state.select(Some(i));
+            None => false,
+        }
     }
 
-    pub fn get_selected_id(&mut self) -> usize {
-        self.selected
+    /**
+    Selects the closest item after the selected one matching `predicate`,
+    or before it when `forward` is false. Wraps around if enabled, coming
+    back to the selected item last.
+    */
+    pub fn select_next_where<P: Fn(&T) -> bool>(&mut self, predicate: P, forward: bool) -> bool {
+        let len = self.view.len();
+        if len == 0 {
+            return false;
+        }
+
+        let start = self.state.selected();
+        let candidates: Vec<usize> = match (start, forward) {
+            (None, true) => (0..len).collect(),
+            (None, false) => (0..len).rev().collect(),
+            (Some(s), true) if self.wrap => (s + 1..len).chain(0..=s).collect(),
+            (Some(s), true) => (s + 1..len).collect(),
+            (Some(s), false) if self.wrap => (0..s).rev().chain((s..len).rev()).collect(),
+            (Some(s), false) => (0..s).rev().collect(),
+        };
+
+        match candidates
+            .into_iter()
+            .find(|i| predicate(&self.items[self.view[*i]]))
+        {
+            Some(i) => {
+                self.state.select(Some(i));
+                true
+            }
+            None => false,
+        }
     }
 
     pub fn unselect(&mut self) {
         self.state.select(None);
     }
 }
+
+impl<T: Clone> StatefulList<T> {
+    /// Appends an item to the store, it becomes visible at the end of the view
+    pub fn push(&mut self, item: T) {
+        Arc::make_mut(&mut self.items).push(item);
+        self.view.push(self.items.len() - 1);
+    }
+
+    /// Removes the visible item at position `i` of the view from the store
+    pub fn remove(&mut self, i: usize) -> Option<T> {
+        let index = self.view.get(i).copied()?;
+        let item = Arc::make_mut(&mut self.items).remove(index);
+
+        self.view.remove(i);
+        self.view
+            .iter_mut()
+            .filter(|v| **v > index)
+            .for_each(|v| *v -= 1);
+
+        if let Some(selected) = self.state.selected() {
+            self.select(selected);
+        }
+
+        Some(item)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn list(len: usize, wrap: bool) -> StatefulList<usize> {
+        let mut list = StatefulList::with_items((0..len).collect());
+        list.set_wrap(wrap);
+        list
+    }
+
+    #[test]
+    fn moves_on_an_empty_list_select_nothing() {
+        for wrap in [true, false] {
+            let mut list = list(0, wrap);
+
+            list.next();
+            assert_eq!(list.selected_index(), None);
+            list.previous();
+            assert_eq!(list.selected_index(), None);
+            list.page_down();
+            assert_eq!(list.selected_index(), None);
+            list.half_page_up();
+            assert_eq!(list.selected_index(), None);
+            list.select_last();
+            assert_eq!(list.selected_index(), None);
+            assert!(!list.select_next_where(|_| true, true));
+        }
+    }
+
+    #[test]
+    fn moves_on_one_item_stay_on_it() {
+        for wrap in [true, false] {
+            let mut list = list(1, wrap);
+
+            list.next();
+            assert_eq!(list.selected_index(), Some(0));
+            list.next();
+            assert_eq!(list.selected_index(), Some(0));
+            list.previous();
+            assert_eq!(list.selected_index(), Some(0));
+            list.page_down();
+            assert_eq!(list.selected_index(), Some(0));
+            list.half_page_up();
+            assert_eq!(list.selected_index(), Some(0));
+            list.select_last();
+            assert_eq!(list.selected_index(), Some(0));
+        }
+    }
+
+    #[test]
+    fn next_wraps_only_when_enabled() {
+        let mut wrapping = list(3, true);
+        wrapping.select_last();
+        wrapping.next();
+        assert_eq!(wrapping.selected_index(), Some(0));
+        wrapping.previous();
+        assert_eq!(wrapping.selected_index(), Some(2));
+
+        let mut stopping = list(3, false);
+        stopping.select_last();
+        stopping.next();
+        assert_eq!(stopping.selected_index(), Some(2));
+        stopping.select_first();
+        stopping.previous();
+        assert_eq!(stopping.selected_index(), Some(0));
+    }
+
+    #[test]
+    fn page_moves_stop_at_the_ends() {
+        for wrap in [true, false] {
+            let mut list = list(10, wrap);
+            list.set_viewport(4);
+            list.select_first();
+
+            list.page_down();
+            assert_eq!(list.selected_index(), Some(4));
+            list.half_page_up();
+            assert_eq!(list.selected_index(), Some(2));
+            list.page_up();
+            assert_eq!(list.selected_index(), Some(0));
+            list.select(8);
+            list.page_down();
+            assert_eq!(list.selected_index(), Some(9));
+        }
+    }
+
+    #[test]
+    fn select_next_where_wraps_only_when_enabled() {
+        let mut wrapping = list(5, true);
+        wrapping.select(3);
+        assert!(wrapping.select_next_where(|i| i % 2 == 0, true));
+        assert_eq!(wrapping.selected_index(), Some(4));
+        assert!(wrapping.select_next_where(|i| i % 2 == 0, true));
+        assert_eq!(wrapping.selected_index(), Some(0));
+        assert!(wrapping.select_next_where(|i| i % 2 == 0, false));
+        assert_eq!(wrapping.selected_index(), Some(4));
+
+        let mut stopping = list(5, false);
+        stopping.select(4);
+        assert!(!stopping.select_next_where(|i| i % 2 == 0, true));
+        assert_eq!(stopping.selected_index(), Some(4));
+        assert!(stopping.select_next_where(|i| i % 2 == 0, false));
+        assert_eq!(stopping.selected_index(), Some(2));
+    }
+
+    #[test]
+    fn select_next_where_comes_back_to_the_only_match() {
+        let mut list = list(3, true);
+        list.select(1);
+
+        assert!(list.select_next_where(|i| *i == 1, true));
+        assert_eq!(list.selected_index(), Some(1));
+        assert!(list.select_next_where(|i| *i == 1, false));
+        assert_eq!(list.selected_index(), Some(1));
+    }
+
+    #[test]
+    fn set_view_keeps_a_visible_selection() {
+        let mut list = list(5, true);
+        list.select(2);
+
+        list.set_view(vec![4, 2, 0]);
+        assert_eq!(list.selected_index(), Some(1));
+        assert_eq!(list.selected(), Some(&2));
+        assert_eq!(list.iter().copied().collect::<Vec<_>>(), [4, 2, 0]);
+    }
+
+    #[test]
+    fn set_view_drops_a_hidden_selection() {
+        let mut list = list(5, true);
+        list.select(2);
+
+        list.set_view(vec![0, 1]);
+        assert_eq!(list.selected_index(), None);
+        assert_eq!(list.all().len(), 5);
+    }
+
+    #[test]
+    fn remove_shifts_the_view() {
+        let mut list = StatefulList::with_items(vec!['a', 'b', 'c', 'd']);
+        list.set_view(vec![3, 1, 2]);
+        list.select(2);
+
+        assert_eq!(list.remove(1), Some('b'));
+        assert_eq!(list.iter().copied().collect::<Vec<_>>(), ['d', 'c']);
+        assert_eq!(list.all(), ['a', 'c', 'd']);
+        assert_eq!(list.selected(), Some(&'c'));
+
+        list.push('e');
+        assert_eq!(list.iter().copied().collect::<Vec<_>>(), ['d', 'c', 'e']);
+    }
+
+    #[test]
+    fn remove_keeps_the_selection_in_the_list() {
+        let mut list = StatefulList::with_items(vec!['a', 'b']);
+        list.select_last();
+
+        assert_eq!(list.remove(1), Some('b'));
+        assert_eq!(list.selected_index(), Some(0));
+        assert_eq!(list.remove(0), Some('a'));
+        assert_eq!(list.selected_index(), None);
+        assert_eq!(list.remove(0), None);
+    }
+}
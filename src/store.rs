@@ -0,0 +1,1095 @@
+use core::fmt;
+use std::{
+    collections::{HashMap, HashSet},
+    io,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use rusqlite::{params, Connection, OptionalExtension, Transaction, TransactionBehavior};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    caching, compression,
+    locale::{fill, messages},
+    parsing::{ApiObjectResult, ClassificationScp, ScpObject},
+    paths,
+};
+
+const STORE_PATH: &str = "store.sqlite";
+/// Kept in `PRAGMA user_version`, every bump adds a step to [`migrate`]
+const SCHEMA_VERSION: i32 = 2;
+/// Key of the `meta` table with the time the objects were scraped
+const CATALOG_FETCHED: &str = "catalog_fetched";
+/// Revisions needed to train the shared dictionary of the sources
+const TRAIN_AFTER: i64 = 32;
+
+/// Schema of version 1, later versions change it in the steps of [`migrate`]
+const SCHEMA: &str = "
+CREATE TABLE objects (
+    id TEXT PRIMARY KEY,
+    -- Order the objects were scraped in
+    position INTEGER NOT NULL,
+    name TEXT NOT NULL,
+    class TEXT NOT NULL
+);
+
+-- Every version of an article seen, a new one only when the source changes
+CREATE TABLE revisions (
+    id INTEGER PRIMARY KEY,
+    page_id TEXT NOT NULL,
+    fetched INTEGER NOT NULL,
+    title TEXT NOT NULL,
+    source TEXT NOT NULL,
+    locked INTEGER NOT NULL,
+    rating INTEGER
+);
+CREATE INDEX revisions_page ON revisions (page_id, id);
+
+-- Tags of the latest revision of every page
+CREATE TABLE tags (
+    page_id TEXT NOT NULL,
+    tag TEXT NOT NULL,
+    PRIMARY KEY (page_id, tag)
+);
+CREATE INDEX tags_tag ON tags (tag);
+
+CREATE TABLE favorites (
+    page_id TEXT PRIMARY KEY,
+    added INTEGER NOT NULL
+);
+
+CREATE TABLE history (
+    id INTEGER PRIMARY KEY,
+    page_id TEXT NOT NULL,
+    opened INTEGER NOT NULL
+);
+CREATE INDEX history_page ON history (page_id);
+
+CREATE TABLE meta (
+    key TEXT PRIMARY KEY,
+    value INTEGER NOT NULL
+);
+";
+
+#[derive(Debug)]
+pub enum StoreError {
+    /// The objects were never scraped
+    NotCached,
+    /// Created by a newer version of the app
+    UnsupportedVersion(i32),
+    /// A source can't be compressed or decompressed
+    Compression(io::Error),
+    Sqlite(rusqlite::Error),
+}
+
+impl std::error::Error for StoreError {}
+
+impl fmt::Display for StoreError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            StoreError::NotCached => write!(f, "{}", messages().store_empty),
+            StoreError::UnsupportedVersion(v) => {
+                write!(f, "{}", fill(messages().store_newer, &[v, &SCHEMA_VERSION]))
+            }
+            StoreError::Compression(e) => write!(f, "{}", fill(messages().store_compression, &[e])),
+            StoreError::Sqlite(e) => write!(f, "{}", fill(messages().store_failed, &[e])),
+        }
+    }
+}
+
+impl From<io::Error> for StoreError {
+    fn from(e: io::Error) -> Self {
+        StoreError::Compression(e)
+    }
+}
+
+impl From<rusqlite::Error> for StoreError {
+    fn from(e: rusqlite::Error) -> Self {
+        StoreError::Sqlite(e)
+    }
+}
+
+fn now() -> i64 {
+    seconds(SystemTime::now())
+}
+
+fn seconds(time: SystemTime) -> i64 {
+    time.duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs() as i64)
+}
+
+/// Time passed since `seconds` after the unix epoch
+fn age(seconds: i64) -> Duration {
+    Duration::from_secs((now() - seconds).max(0) as u64)
+}
+
+/**
+Opens the store in the data directory, creating or upgrading its schema.
+Every call gets its own connection, so the loading task and the interface
+never share one; SQLite serializes the writers itself.
+*/
+fn open() -> Result<Connection, StoreError> {
+    let mut conn = Connection::open(paths::data_file(STORE_PATH))?;
+    conn.busy_timeout(Duration::from_secs(5))?;
+    migrate(&mut conn)?;
+
+    Ok(conn)
+}
+
+fn migrate(conn: &mut Connection) -> Result<(), StoreError> {
+    let version: i32 = conn.query_row("PRAGMA user_version", [], |r| r.get(0))?;
+
+    match version {
+        SCHEMA_VERSION => return Ok(()),
+        v if v > SCHEMA_VERSION => return Err(StoreError::UnsupportedVersion(v)),
+        _ => {}
+    }
+
+    // Taking the write lock first, another instance may be migrating right now
+    let tx = conn.transaction_with_behavior(TransactionBehavior::Immediate)?;
+    let version: i32 = tx.query_row("PRAGMA user_version", [], |r| r.get(0))?;
+    if version == 0 {
+        tx.execute_batch(SCHEMA)?;
+    }
+    if version < 2 {
+        compress_revisions(&tx)?;
+    }
+    if version == 0 {
+        import_legacy(&tx)?;
+    }
+    tx.pragma_update(None, "user_version", SCHEMA_VERSION)?;
+    tx.commit()?;
+
+    // Only removed once they are safely in the store
+    caching::remove_legacy();
+
+    Ok(())
+}
+
+/// Version 2 keeps the sources compressed, see [`crate::compression`]
+fn compress_revisions(tx: &Transaction) -> Result<(), StoreError> {
+    tx.execute_batch(
+        "ALTER TABLE revisions RENAME TO revisions_v1;
+
+        CREATE TABLE dictionaries (
+            id INTEGER PRIMARY KEY,
+            data BLOB NOT NULL
+        );
+
+        CREATE TABLE revisions (
+            id INTEGER PRIMARY KEY,
+            page_id TEXT NOT NULL,
+            fetched INTEGER NOT NULL,
+            title TEXT NOT NULL,
+            -- zstd, with the dictionary when it isn't null
+            source BLOB NOT NULL,
+            raw_size INTEGER NOT NULL,
+            -- CRC32 of the source before compression
+            checksum INTEGER NOT NULL,
+            dictionary INTEGER REFERENCES dictionaries (id),
+            locked INTEGER NOT NULL,
+            rating INTEGER
+        );",
+    )?;
+
+    {
+        let mut select = tx.prepare(
+            "SELECT id, page_id, fetched, title, source, locked, rating FROM revisions_v1",
+        )?;
+        let mut insert = tx.prepare(
+            "INSERT INTO revisions
+             (id, page_id, fetched, title, source, raw_size, checksum, locked, rating)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+        )?;
+
+        let mut rows = select.query([])?;
+        while let Some(r) = rows.next()? {
+            let source: String = r.get(4)?;
+            insert.execute(params![
+                r.get::<_, i64>(0)?,
+                r.get::<_, String>(1)?,
+                r.get::<_, i64>(2)?,
+                r.get::<_, String>(3)?,
+                compression::compress(source.as_bytes(), None)?,
+                source.len() as i64,
+                crc32fast::hash(source.as_bytes()),
+                r.get::<_, bool>(5)?,
+                r.get::<_, Option<i32>>(6)?,
+            ])?;
+        }
+    }
+
+    tx.execute_batch(
+        "DROP TABLE revisions_v1;
+        CREATE INDEX revisions_page ON revisions (page_id, id);",
+    )?;
+
+    train_dictionary(tx)
+}
+
+/// One-time import of the bincode caches written before the store
+fn import_legacy(tx: &Transaction) -> Result<(), StoreError> {
+    if let Ok((objects, created)) = caching::legacy_objects() {
+        insert_objects(tx, &objects, seconds(created))?;
+    }
+
+    for (article, created) in caching::legacy_articles() {
+        insert_article(tx, &article.page_id, &article, seconds(created))?;
+    }
+
+    Ok(())
+}
+
+fn insert_objects(tx: &Transaction, objects: &[ScpObject], fetched: i64) -> Result<(), StoreError> {
+    tx.execute("DELETE FROM objects", [])?;
+
+    let mut insert = tx.prepare(
+        "INSERT OR REPLACE INTO objects (id, position, name, class) VALUES (?1, ?2, ?3, ?4)",
+    )?;
+    for (position, object) in objects.iter().enumerate() {
+        insert.execute(params![
+            object.get_id(),
+            position as i64,
+            object.get_name(),
+            object.get_class().key(),
+        ])?;
+    }
+
+    tx.execute(
+        "INSERT OR REPLACE INTO meta (key, value) VALUES (?1, ?2)",
+        params![CATALOG_FETCHED, fetched],
+    )?;
+
+    Ok(())
+}
+
+/// The shared dictionary, `None` until enough articles were cached to train it
+fn dictionary(conn: &Connection) -> Result<Option<(i64, Vec<u8>)>, StoreError> {
+    let dictionary = conn
+        .query_row(
+            "SELECT id, data FROM dictionaries ORDER BY id DESC LIMIT 1",
+            [],
+            |r| Ok((r.get(0)?, r.get(1)?)),
+        )
+        .optional()?;
+
+    Ok(dictionary)
+}
+
+/**
+Trains the shared dictionary once there are enough articles and compresses
+every article again with it, nothing changes when the samples are too poor
+*/
+fn train_dictionary(tx: &Transaction) -> Result<(), StoreError> {
+    let count: i64 = tx.query_row("SELECT count(*) FROM revisions", [], |r| r.get(0))?;
+    if count < TRAIN_AFTER || dictionary(tx)?.is_some() {
+        return Ok(());
+    }
+
+    let revisions = {
+        let mut select = tx.prepare("SELECT id, source, raw_size FROM revisions")?;
+        let rows = select.query_map([], |r| {
+            Ok((
+                r.get::<_, i64>(0)?,
+                r.get::<_, Vec<u8>>(1)?,
+                r.get::<_, i64>(2)?,
+            ))
+        })?;
+
+        let mut revisions = Vec::new();
+        for row in rows {
+            let (id, source, raw_size) = row?;
+            revisions.push((
+                id,
+                compression::decompress(&source, raw_size as usize, None)?,
+            ));
+        }
+        revisions
+    };
+
+    let samples: Vec<&[u8]> = revisions.iter().map(|(_, s)| s.as_slice()).collect();
+    let Ok(data) = compression::train(&samples) else {
+        return Ok(());
+    };
+
+    tx.execute("INSERT INTO dictionaries (data) VALUES (?1)", [&data])?;
+    let dictionary = tx.last_insert_rowid();
+
+    let mut update =
+        tx.prepare("UPDATE revisions SET source = ?2, dictionary = ?3 WHERE id = ?1")?;
+    for (id, source) in &revisions {
+        let source = compression::compress(source, Some(&data))?;
+        update.execute(params![id, source, dictionary])?;
+    }
+
+    Ok(())
+}
+
+/// Adds a revision when the source changed since the latest one
+fn insert_article(
+    tx: &Transaction,
+    page_id: &str,
+    article: &ApiObjectResult,
+    fetched: i64,
+) -> Result<(), StoreError> {
+    let checksum = crc32fast::hash(article.source.as_bytes());
+    let latest: Option<(i64, u32)> = tx
+        .query_row(
+            "SELECT id, checksum FROM revisions WHERE page_id = ?1 ORDER BY id DESC LIMIT 1",
+            [page_id],
+            |r| Ok((r.get(0)?, r.get(1)?)),
+        )
+        .optional()?;
+
+    match latest {
+        Some((id, latest)) if latest == checksum => {
+            tx.execute(
+                "UPDATE revisions SET fetched = ?2, title = ?3, locked = ?4, rating = ?5
+                 WHERE id = ?1",
+                params![id, fetched, article.title, article.locked, article.rating],
+            )?;
+        }
+        _ => {
+            let dictionary = dictionary(tx)?;
+            let source = compression::compress(
+                article.source.as_bytes(),
+                dictionary.as_ref().map(|(_, d)| d.as_slice()),
+            )?;
+
+            tx.execute(
+                "INSERT INTO revisions
+                 (page_id, fetched, title, source, raw_size, checksum, dictionary, locked, rating)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+                params![
+                    page_id,
+                    fetched,
+                    article.title,
+                    source,
+                    article.source.len() as i64,
+                    checksum,
+                    dictionary.map(|(id, _)| id),
+                    article.locked,
+                    article.rating
+                ],
+            )?;
+
+            // A failed training is retried after as many revisions more, not on every write
+            if tx.last_insert_rowid() % TRAIN_AFTER == 0 {
+                train_dictionary(tx)?;
+            }
+        }
+    }
+
+    tx.execute("DELETE FROM tags WHERE page_id = ?1", [page_id])?;
+    let mut insert = tx.prepare("INSERT OR IGNORE INTO tags (page_id, tag) VALUES (?1, ?2)")?;
+    for tag in &article.tags {
+        insert.execute(params![page_id, tag])?;
+    }
+
+    Ok(())
+}
+
+pub fn cache_objects(objects: &[ScpObject]) -> Result<(), StoreError> {
+    let mut conn = open()?;
+    let tx = conn.transaction()?;
+    insert_objects(&tx, objects, now())?;
+    tx.commit()?;
+
+    Ok(())
+}
+
+pub fn decache_objects() -> Result<Vec<ScpObject>, StoreError> {
+    let conn = open()?;
+    if objects_fetched(&conn)?.is_none() {
+        return Err(StoreError::NotCached);
+    }
+
+    read_objects(&conn)
+}
+
+fn read_objects(conn: &Connection) -> Result<Vec<ScpObject>, StoreError> {
+    let mut select = conn.prepare("SELECT id, name, class FROM objects ORDER BY position")?;
+    let objects = select
+        .query_map([], |r| {
+            let class: String = r.get(2)?;
+            Ok(ScpObject::new(
+                ClassificationScp::from_name(&class).unwrap_or(ClassificationScp::None),
+                r.get(1)?,
+                r.get(0)?,
+            ))
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(objects)
+}
+
+/// Seconds since the unix epoch when the objects were scraped
+fn objects_fetched(conn: &Connection) -> Result<Option<i64>, StoreError> {
+    let fetched = conn
+        .query_row(
+            "SELECT value FROM meta WHERE key = ?1",
+            [CATALOG_FETCHED],
+            |r| r.get(0),
+        )
+        .optional()?;
+
+    Ok(fetched)
+}
+
+/// Time since the objects were cached, `None` without a cache
+pub fn objects_age() -> Option<Duration> {
+    let fetched = objects_fetched(&open().ok()?).ok()??;
+
+    Some(age(fetched))
+}
+
+pub fn cache_article(page_id: &str, article: &ApiObjectResult) -> Result<(), StoreError> {
+    let mut conn = open()?;
+    let tx = conn.transaction()?;
+    insert_article(&tx, page_id, article, now())?;
+    tx.commit()?;
+
+    Ok(())
+}
+
+/// The latest revision of the article and how long ago it was loaded
+pub fn decache_article(page_id: &str) -> Result<(ApiObjectResult, Duration), StoreError> {
+    let (article, fetched) = read_article(&open()?, page_id)?;
+
+    Ok((article, age(fetched)))
+}
+
+/// The latest revision with the time it was loaded in seconds since the unix epoch
+fn read_article(conn: &Connection, page_id: &str) -> Result<(ApiObjectResult, i64), StoreError> {
+    let (title, source, raw_size, dictionary, locked, rating, fetched) = conn
+        .query_row(
+            "SELECT r.title, r.source, r.raw_size, d.data, r.locked, r.rating, r.fetched
+             FROM revisions r LEFT JOIN dictionaries d ON d.id = r.dictionary
+             WHERE r.page_id = ?1 ORDER BY r.id DESC LIMIT 1",
+            [page_id],
+            |r| {
+                Ok((
+                    r.get::<_, String>(0)?,
+                    r.get::<_, Vec<u8>>(1)?,
+                    r.get::<_, i64>(2)?,
+                    r.get::<_, Option<Vec<u8>>>(3)?,
+                    r.get(4)?,
+                    r.get(5)?,
+                    r.get::<_, i64>(6)?,
+                ))
+            },
+        )
+        .optional()?
+        .ok_or(StoreError::NotCached)?;
+
+    let source = compression::decompress(&source, raw_size as usize, dictionary.as_deref())?;
+    let source =
+        String::from_utf8(source).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+
+    let mut select = conn.prepare("SELECT tag FROM tags WHERE page_id = ?1 ORDER BY tag")?;
+    let tags = select
+        .query_map([page_id], |r| r.get(0))?
+        .collect::<Result<Vec<String>, _>>()?;
+
+    let article = ApiObjectResult {
+        page_id: page_id.to_string(),
+        title,
+        source,
+        tags,
+        locked,
+        rating,
+    };
+
+    Ok((article, fetched))
+}
+
+/// Ratings of the latest revisions by the id of the object
+pub fn ratings() -> Result<HashMap<String, i32>, StoreError> {
+    let conn = open()?;
+    let mut select = conn.prepare(
+        "SELECT page_id, rating FROM revisions
+         WHERE rating IS NOT NULL
+           AND id IN (SELECT max(id) FROM revisions GROUP BY page_id)",
+    )?;
+    let ratings = select
+        .query_map([], |r| Ok((r.get::<_, String>(0)?, r.get(1)?)))?
+        .filter_map(|r| r.ok())
+        .filter_map(|(page, rating)| Some((page.strip_prefix("scp-")?.to_string(), rating)))
+        .collect();
+
+    Ok(ratings)
+}
+
+/// Adds the page to the favorites or removes it, `true` when it was added
+pub fn toggle_favorite(page_id: &str) -> Result<bool, StoreError> {
+    let conn = open()?;
+    if conn.execute("DELETE FROM favorites WHERE page_id = ?1", [page_id])? > 0 {
+        return Ok(false);
+    }
+
+    conn.execute(
+        "INSERT INTO favorites (page_id, added) VALUES (?1, ?2)",
+        params![page_id, now()],
+    )?;
+
+    Ok(true)
+}
+
+pub fn favorites() -> Result<HashSet<String>, StoreError> {
+    page_ids("SELECT page_id FROM favorites")
+}
+
+pub fn record_visit(page_id: &str) -> Result<(), StoreError> {
+    open()?.execute(
+        "INSERT INTO history (page_id, opened) VALUES (?1, ?2)",
+        params![page_id, now()],
+    )?;
+
+    Ok(())
+}
+
+/// Every page opened at least once
+pub fn visited() -> Result<HashSet<String>, StoreError> {
+    page_ids("SELECT DISTINCT page_id FROM history")
+}
+
+fn page_ids(sql: &str) -> Result<HashSet<String>, StoreError> {
+    let conn = open()?;
+    let mut select = conn.prepare(sql)?;
+    let ids = select
+        .query_map([], |r| r.get(0))?
+        .collect::<Result<HashSet<String>, _>>()?;
+
+    Ok(ids)
+}
+
+/// Cached article as listed by the `cache` command
+pub struct Entry {
+    pub page_id: String,
+    /// Bytes of the sources of every revision before compression
+    pub raw_size: u64,
+    /// Bytes of the sources as they are stored
+    pub size: u64,
+    pub revisions: usize,
+    /// Since the latest revision was loaded
+    pub age: Duration,
+}
+
+/// Number of the cached objects and their age, `None` when they weren't scraped yet
+pub fn catalog_entry() -> Result<Option<(usize, Duration)>, StoreError> {
+    let Some(age) = objects_age() else {
+        return Ok(None);
+    };
+
+    let count: i64 = open()?.query_row("SELECT count(*) FROM objects", [], |r| r.get(0))?;
+
+    Ok(Some((count as usize, age)))
+}
+
+/// Every cached article sorted by its page id
+pub fn article_entries() -> Result<Vec<Entry>, StoreError> {
+    let conn = open()?;
+    let mut select = conn.prepare(
+        "SELECT page_id, sum(raw_size), sum(length(source)), count(*), max(fetched)
+         FROM revisions GROUP BY page_id ORDER BY page_id",
+    )?;
+    let entries = select
+        .query_map([], |r| {
+            Ok(Entry {
+                page_id: r.get(0)?,
+                raw_size: r.get::<_, i64>(1)? as u64,
+                size: r.get::<_, i64>(2)? as u64,
+                revisions: r.get::<_, i64>(3)? as usize,
+                age: age(r.get(4)?),
+            })
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(entries)
+}
+
+/// Size of the shared dictionary, `None` until it is trained
+pub fn dictionary_size() -> Result<Option<u64>, StoreError> {
+    let size: Option<i64> = open()?
+        .query_row(
+            "SELECT length(data) FROM dictionaries ORDER BY id DESC LIMIT 1",
+            [],
+            |r| r.get(0),
+        )
+        .optional()?;
+
+    Ok(size.map(|s| s as u64))
+}
+
+/// Removes every revision of the given pages with their tags
+pub fn remove_articles(page_ids: &[&str]) -> Result<(), StoreError> {
+    let mut conn = open()?;
+    let tx = conn.transaction()?;
+    for page_id in page_ids {
+        tx.execute("DELETE FROM revisions WHERE page_id = ?1", [page_id])?;
+        tx.execute("DELETE FROM tags WHERE page_id = ?1", [page_id])?;
+    }
+    tx.commit()?;
+
+    Ok(())
+}
+
+/// Removes the objects, the articles and their dictionary, favorites and history are kept
+pub fn clear() -> Result<(), StoreError> {
+    open()?.execute_batch(
+        "BEGIN;
+         DELETE FROM objects;
+         DELETE FROM revisions;
+         DELETE FROM dictionaries;
+         DELETE FROM tags;
+         DELETE FROM meta;
+         COMMIT;
+         VACUUM;",
+    )?;
+
+    Ok(())
+}
+
+/// Everything an archive carries, see [`crate::archive`]
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct Snapshot {
+    pub objects: Vec<ScpObject>,
+    /// Seconds since the unix epoch when the objects were scraped, `0` without them
+    pub objects_fetched: i64,
+    /// The latest revision of every article with the time it was loaded
+    pub articles: Vec<(ApiObjectResult, i64)>,
+    /// Page ids, empty unless they were asked for
+    pub favorites: Vec<String>,
+}
+
+/// What [`merge`] took from a snapshot
+#[derive(Debug, Default)]
+pub struct MergeReport {
+    pub objects: usize,
+    pub articles: usize,
+    pub favorites: usize,
+}
+
+/// The objects and the latest revision of every article, with the favorites when asked
+pub fn snapshot(favorites: bool) -> Result<Snapshot, StoreError> {
+    let conn = open()?;
+
+    let page_ids: Vec<String> = conn
+        .prepare("SELECT DISTINCT page_id FROM revisions ORDER BY page_id")?
+        .query_map([], |r| r.get(0))?
+        .collect::<Result<_, _>>()?;
+    let articles = page_ids
+        .iter()
+        .map(|page_id| read_article(&conn, page_id))
+        .collect::<Result<_, _>>()?;
+
+    let favorites = if favorites {
+        conn.prepare("SELECT page_id FROM favorites ORDER BY added")?
+            .query_map([], |r| r.get(0))?
+            .collect::<Result<_, _>>()?
+    } else {
+        Vec::new()
+    };
+
+    Ok(Snapshot {
+        objects: read_objects(&conn)?,
+        objects_fetched: objects_fetched(&conn)?.unwrap_or_default(),
+        articles,
+        favorites,
+    })
+}
+
+/**
+Adds a snapshot to the store, whatever is newer wins: the objects replace the
+local ones only when they were scraped later, an article is added only when
+it was loaded after the local revision. Favorites are added to the local ones.
+*/
+pub fn merge(snapshot: &Snapshot) -> Result<MergeReport, StoreError> {
+    let mut conn = open()?;
+    let tx = conn.transaction_with_behavior(TransactionBehavior::Immediate)?;
+    let mut report = MergeReport::default();
+
+    let newer = objects_fetched(&tx)?.is_none_or(|local| local < snapshot.objects_fetched);
+    if !snapshot.objects.is_empty() && newer {
+        insert_objects(&tx, &snapshot.objects, snapshot.objects_fetched)?;
+        report.objects = snapshot.objects.len();
+    }
+
+    for (article, fetched) in &snapshot.articles {
+        let local: Option<i64> = tx.query_row(
+            "SELECT max(fetched) FROM revisions WHERE page_id = ?1",
+            [&article.page_id],
+            |r| r.get(0),
+        )?;
+
+        if local.is_none_or(|local| local < *fetched) {
+            insert_article(&tx, &article.page_id, article, *fetched)?;
+            report.articles += 1;
+        }
+    }
+
+    for page_id in &snapshot.favorites {
+        report.favorites += tx.execute(
+            "INSERT OR IGNORE INTO favorites (page_id, added) VALUES (?1, ?2)",
+            params![page_id, now()],
+        )?;
+    }
+
+    tx.commit()?;
+
+    Ok(report)
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use std::{
+        fs,
+        sync::{Mutex, MutexGuard},
+    };
+
+    use super::*;
+    use crate::paths::Paths;
+
+    /**
+    Points the paths of the app to a temporary directory and empties it. The
+    paths are global, so the tests using the store run one at a time.
+    */
+    pub(crate) fn temp_store() -> MutexGuard<'static, ()> {
+        static LOCK: Mutex<()> = Mutex::new(());
+        let guard = LOCK.lock().unwrap_or_else(|e| e.into_inner());
+
+        let root = std::env::temp_dir().join(format!(
+            "scpfoundation-explorer-store-{}",
+            std::process::id()
+        ));
+        paths::init(Paths {
+            config: root.join("config.toml"),
+            data: root.join("data"),
+            cache: root.join("cache"),
+        });
+        let _ = fs::remove_dir_all(&root);
+
+        guard
+    }
+
+    pub(crate) fn object(id: &str) -> ScpObject {
+        ScpObject::new(
+            ClassificationScp::Keter,
+            format!("SCP-{}", id),
+            id.to_string(),
+        )
+    }
+
+    pub(crate) fn article(page_id: &str, source: &str) -> ApiObjectResult {
+        ApiObjectResult {
+            page_id: page_id.to_string(),
+            title: page_id.to_uppercase(),
+            source: source.to_string(),
+            tags: vec![String::from("keter"), String::from("alive")],
+            locked: false,
+            rating: Some(42),
+        }
+    }
+
+    fn ids(objects: &[ScpObject]) -> Vec<String> {
+        objects.iter().map(|o| o.get_id()).collect()
+    }
+
+    fn user_version() -> i32 {
+        Connection::open(paths::data_file(STORE_PATH))
+            .unwrap()
+            .query_row("PRAGMA user_version", [], |r| r.get(0))
+            .unwrap()
+    }
+
+    #[test]
+    fn new_store_gets_the_current_schema() {
+        let _store = temp_store();
+
+        assert!(matches!(decache_objects(), Err(StoreError::NotCached)));
+        assert_eq!(user_version(), SCHEMA_VERSION);
+    }
+
+    #[test]
+    fn rejects_a_newer_schema() {
+        let _store = temp_store();
+        Connection::open(paths::data_file(STORE_PATH))
+            .unwrap()
+            .pragma_update(None, "user_version", SCHEMA_VERSION + 1)
+            .unwrap();
+
+        assert!(matches!(
+            decache_objects(),
+            Err(StoreError::UnsupportedVersion(v)) if v == SCHEMA_VERSION + 1
+        ));
+    }
+
+    #[test]
+    fn imports_the_legacy_caches_once() {
+        let _store = temp_store();
+        // Names of the caches written before the store
+        let objects = paths::cache_file("cache_o.data");
+        fs::write(
+            &objects,
+            bincode::serialize(&vec![object("002"), object("173")]).unwrap(),
+        )
+        .unwrap();
+        let articles = paths::cache_dir("articles");
+        fs::write(
+            articles.join("scp-173.data"),
+            bincode::serialize(&article("scp-173", "Statue")).unwrap(),
+        )
+        .unwrap();
+
+        assert_eq!(ids(&decache_objects().unwrap()), ["002", "173"]);
+        assert_eq!(decache_article("scp-173").unwrap().0.source, "Statue");
+        assert!(!objects.exists());
+        assert!(!articles.exists());
+    }
+
+    #[test]
+    fn imports_the_objects_cached_in_the_current_directory() {
+        let _store = temp_store();
+        let dir = paths::cache_dir("working_directory");
+        let previous = std::env::current_dir().unwrap();
+        std::env::set_current_dir(&dir).unwrap();
+        fs::write(
+            "cache_o.data",
+            bincode::serialize(&vec![object("173")]).unwrap(),
+        )
+        .unwrap();
+
+        let objects = decache_objects();
+        std::env::set_current_dir(previous).unwrap();
+
+        assert_eq!(ids(&objects.unwrap()), ["173"]);
+        assert!(!dir.join("cache_o.data").exists());
+        assert!(!dir.join("cache_o.lock").exists());
+    }
+
+    #[test]
+    fn caches_objects_in_their_order() {
+        let _store = temp_store();
+
+        cache_objects(&[object("173"), object("002")]).unwrap();
+        assert_eq!(ids(&decache_objects().unwrap()), ["173", "002"]);
+        assert!(objects_age().unwrap() < Duration::from_secs(60));
+
+        cache_objects(&[object("049")]).unwrap();
+        assert_eq!(ids(&decache_objects().unwrap()), ["049"]);
+    }
+
+    #[test]
+    fn caches_articles_with_tags_and_ratings() {
+        let _store = temp_store();
+
+        cache_article("scp-173", &article("scp-173", "Statue")).unwrap();
+        let (cached, age) = decache_article("scp-173").unwrap();
+
+        assert_eq!(cached.title, "SCP-173");
+        assert_eq!(cached.tags, ["alive", "keter"]);
+        assert!(age < Duration::from_secs(60));
+        assert_eq!(ratings().unwrap().get("173"), Some(&42));
+        assert!(matches!(
+            decache_article("scp-049"),
+            Err(StoreError::NotCached)
+        ));
+    }
+
+    #[test]
+    fn keeps_a_revision_per_changed_source() {
+        let _store = temp_store();
+
+        cache_article("scp-173", &article("scp-173", "Statue")).unwrap();
+        cache_article("scp-173", &article("scp-173", "Statue")).unwrap();
+        cache_article("scp-173", &article("scp-173", "Moving statue")).unwrap();
+
+        assert_eq!(article_entries().unwrap()[0].revisions, 2);
+        assert_eq!(
+            decache_article("scp-173").unwrap().0.source,
+            "Moving statue"
+        );
+    }
+
+    #[test]
+    fn toggles_favorites_and_records_visits() {
+        let _store = temp_store();
+
+        assert!(toggle_favorite("scp-173").unwrap());
+        record_visit("scp-049").unwrap();
+        record_visit("scp-049").unwrap();
+        assert_eq!(
+            favorites().unwrap(),
+            HashSet::from([String::from("scp-173")])
+        );
+        assert_eq!(visited().unwrap(), HashSet::from([String::from("scp-049")]));
+
+        assert!(!toggle_favorite("scp-173").unwrap());
+        assert!(favorites().unwrap().is_empty());
+    }
+
+    #[test]
+    fn clear_keeps_the_user_data() {
+        let _store = temp_store();
+        cache_objects(&[object("173")]).unwrap();
+        cache_article("scp-173", &article("scp-173", "Statue")).unwrap();
+        toggle_favorite("scp-173").unwrap();
+
+        clear().unwrap();
+
+        assert!(matches!(decache_objects(), Err(StoreError::NotCached)));
+        assert!(article_entries().unwrap().is_empty());
+        assert_eq!(favorites().unwrap().len(), 1);
+    }
+
+    /// Articles sharing their markup as the ones of the site do
+    fn articles(count: usize) -> Vec<ApiObjectResult> {
+        let words = [
+            "statue",
+            "moves",
+            "when",
+            "unobserved",
+            "containment",
+            "cell",
+            "staff",
+        ];
+
+        (0..count)
+            .map(|i| {
+                let body: Vec<String> = (0..200)
+                    .map(|j| words[(i * 7 + j * 3 + j / 5) % words.len()].to_string())
+                    .collect();
+                let source = format!(
+                    "[[>]]\n[[module Rate]]\n[[/>]]\n**Object number:** SCP-{:03}\n\n**Object class:** Keter\n\n{}",
+                    i,
+                    body.join(" ")
+                );
+                article(&format!("scp-{:03}", i), &source)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn compresses_the_sources_of_a_first_version_store() {
+        let _store = temp_store();
+        {
+            let conn = Connection::open(paths::data_file(STORE_PATH)).unwrap();
+            conn.execute_batch(SCHEMA).unwrap();
+            conn.execute(
+                "INSERT INTO revisions (page_id, fetched, title, source, locked, rating)
+                 VALUES ('scp-173', ?1, 'SCP-173', 'Statue', 0, 7)",
+                [now()],
+            )
+            .unwrap();
+            conn.pragma_update(None, "user_version", 1).unwrap();
+        }
+
+        let (article, _) = decache_article("scp-173").unwrap();
+
+        assert_eq!(article.source, "Statue");
+        assert_eq!(article.rating, Some(7));
+        assert_eq!(user_version(), SCHEMA_VERSION);
+        assert_eq!(dictionary_size().unwrap(), None);
+    }
+
+    #[test]
+    fn trains_the_dictionary_after_enough_articles() {
+        let _store = temp_store();
+        let articles = articles(TRAIN_AFTER as usize + 1);
+
+        for article in &articles[..TRAIN_AFTER as usize - 1] {
+            cache_article(&article.page_id, article).unwrap();
+        }
+        assert_eq!(dictionary_size().unwrap(), None);
+
+        for article in &articles[TRAIN_AFTER as usize - 1..] {
+            cache_article(&article.page_id, article).unwrap();
+        }
+        assert!(dictionary_size().unwrap().is_some());
+
+        // Articles written before and after the training read back the same
+        for article in [&articles[0], articles.last().unwrap()] {
+            let (cached, _) = decache_article(&article.page_id).unwrap();
+            assert_eq!(cached.source, article.source);
+        }
+        let entries = article_entries().unwrap();
+        assert!(entries.iter().all(|e| e.size < e.raw_size));
+    }
+
+    #[test]
+    fn snapshot_has_favorites_only_when_asked() {
+        let _store = temp_store();
+        cache_objects(&[object("173")]).unwrap();
+        cache_article("scp-173", &article("scp-173", "Statue")).unwrap();
+        toggle_favorite("scp-173").unwrap();
+
+        let without = snapshot(false).unwrap();
+        assert_eq!(ids(&without.objects), ["173"]);
+        assert_eq!(without.articles.len(), 1);
+        assert!(without.favorites.is_empty());
+
+        assert_eq!(snapshot(true).unwrap().favorites, ["scp-173"]);
+    }
+
+    #[test]
+    fn merge_takes_what_is_newer() {
+        let _store = temp_store();
+        cache_objects(&[object("173")]).unwrap();
+        cache_article("scp-173", &article("scp-173", "Local")).unwrap();
+        cache_article("scp-049", &article("scp-049", "Local")).unwrap();
+        toggle_favorite("scp-173").unwrap();
+
+        let snapshot = Snapshot {
+            objects: vec![object("002")],
+            objects_fetched: now() - 100,
+            articles: vec![
+                (article("scp-173", "Older"), now() - 100),
+                (article("scp-049", "Newer"), now() + 100),
+                (article("scp-002", "New"), now() - 100),
+            ],
+            favorites: vec![String::from("scp-173"), String::from("scp-002")],
+        };
+        let report = merge(&snapshot).unwrap();
+
+        assert_eq!(report.objects, 0);
+        assert_eq!(report.articles, 2);
+        assert_eq!(report.favorites, 1);
+        assert_eq!(ids(&decache_objects().unwrap()), ["173"]);
+        assert_eq!(decache_article("scp-173").unwrap().0.source, "Local");
+        assert_eq!(decache_article("scp-049").unwrap().0.source, "Newer");
+        assert_eq!(decache_article("scp-002").unwrap().0.source, "New");
+        assert_eq!(favorites().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn merge_replaces_older_objects() {
+        let _store = temp_store();
+        cache_objects(&[object("173")]).unwrap();
+
+        let snapshot = Snapshot {
+            objects: vec![object("002"), object("049")],
+            objects_fetched: now() + 100,
+            ..Snapshot::default()
+        };
+
+        assert_eq!(merge(&snapshot).unwrap().objects, 2);
+        assert_eq!(ids(&decache_objects().unwrap()), ["002", "049"]);
+    }
+
+    #[test]
+    fn merge_into_an_empty_store_takes_everything() {
+        let _store = temp_store();
+        let snapshot = Snapshot {
+            objects: vec![object("173")],
+            objects_fetched: 1,
+            articles: vec![(article("scp-173", "Statue"), 1)],
+            favorites: Vec::new(),
+        };
+
+        let report = merge(&snapshot).unwrap();
+
+        assert_eq!((report.objects, report.articles), (1, 1));
+        assert!(objects_age().unwrap() > Duration::from_secs(60));
+        assert_eq!(merge(&snapshot).unwrap().articles, 0);
+    }
+}
@@ -0,0 +1,186 @@
+use serde::{Deserialize, Serialize};
+
+use crate::article::Article;
+
+/// Articles open in the explorer, each keeps its own scroll and selection
+#[derive(Debug, Clone, Default)]
+pub struct Tabs {
+    tabs: Vec<Article>,
+    active: usize,
+    /// Tabs shown side by side, left and right, one of them is the active one
+    compare: Option<(usize, usize)>,
+    /// Whether the sides of the comparison scroll separately
+    pub independent_scroll: bool,
+}
+
+impl Tabs {
+    pub fn is_empty(&self) -> bool {
+        self.tabs.is_empty()
+    }
+
+    pub fn len(&self) -> usize {
+        self.tabs.len()
+    }
+
+    pub fn active(&self) -> usize {
+        self.active
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Article> {
+        self.tabs.iter()
+    }
+
+    pub fn current(&self) -> Option<&Article> {
+        self.tabs.get(self.active)
+    }
+
+    pub fn current_mut(&mut self) -> Option<&mut Article> {
+        self.tabs.get_mut(self.active)
+    }
+
+    pub fn get_mut(&mut self, i: usize) -> Option<&mut Article> {
+        self.tabs.get_mut(i)
+    }
+
+    /// Left and right tabs when two articles are compared
+    pub fn sides(&self) -> Option<(usize, usize)> {
+        self.compare
+    }
+
+    /// The compared tab which is not active
+    pub fn other(&self) -> Option<usize> {
+        self.compare
+            .map(|(l, r)| if l == self.active { r } else { l })
+    }
+
+    pub fn other_mut(&mut self) -> Option<&mut Article> {
+        self.other().and_then(|i| self.tabs.get_mut(i))
+    }
+
+    /**
+    Shows the active tab side by side with the next one, or stops comparing.
+    Returns whether the tabs are compared now, it takes at least two tabs.
+    */
+    pub fn toggle_compare(&mut self) -> bool {
+        if self.compare.is_some() || self.tabs.len() < 2 {
+            self.compare = None;
+            return false;
+        }
+
+        self.compare = Some(if self.active + 1 < self.tabs.len() {
+            (self.active, self.active + 1)
+        } else {
+            (self.active - 1, self.active)
+        });
+
+        true
+    }
+
+    /// Shows the article in the active tab, opens the first tab if there are none
+    pub fn open(&mut self, article: Article) {
+        match self.tabs.get_mut(self.active) {
+            Some(tab) => *tab = article,
+            None => self.open_new(article),
+        }
+    }
+
+    /// Opens the article in a new tab next to the active one and switches to it
+    pub fn open_new(&mut self, article: Article) {
+        let i = if self.tabs.is_empty() {
+            0
+        } else {
+            self.active + 1
+        };
+
+        self.tabs.insert(i, article);
+        self.active = i;
+        self.compare = None;
+    }
+
+    /// Closes the active tab, the one on its left becomes active
+    pub fn close(&mut self) {
+        if self.tabs.is_empty() {
+            return;
+        }
+
+        self.tabs.remove(self.active);
+        self.active = self.active.saturating_sub(1);
+        self.compare = None;
+    }
+
+    /// Switches to the tab, a tab outside of the comparison ends it
+    pub fn select(&mut self, i: usize) {
+        if i < self.tabs.len() {
+            self.active = i;
+        }
+
+        if self.compare.is_some_and(|(l, r)| i != l && i != r) {
+            self.compare = None;
+        }
+    }
+
+    /// Switches to the next tab, or to the other side while comparing
+    pub fn next(&mut self) {
+        if let Some(other) = self.other() {
+            self.active = other;
+        } else if !self.tabs.is_empty() {
+            self.active = (self.active + 1) % self.tabs.len();
+        }
+    }
+
+    pub fn previous(&mut self) {
+        if let Some(other) = self.other() {
+            self.active = other;
+        } else if !self.tabs.is_empty() {
+            self.active = (self.active + self.tabs.len() - 1) % self.tabs.len();
+        }
+    }
+}
+
+/// Tab as it is stored between runs, the article itself is saved so it opens offline
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SavedTab {
+    pub page_id: String,
+    pub title: String,
+    pub source: String,
+    pub scroll: u16,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct SavedTabs {
+    pub tabs: Vec<SavedTab>,
+    pub active: usize,
+}
+
+impl From<&Tabs> for SavedTabs {
+    fn from(tabs: &Tabs) -> Self {
+        SavedTabs {
+            tabs: tabs
+                .iter()
+                .map(|a| SavedTab {
+                    page_id: a.page_id.clone(),
+                    title: a.title.clone(),
+                    source: a.raw().to_string(),
+                    scroll: a.scroll.0,
+                })
+                .collect(),
+            active: tabs.active(),
+        }
+    }
+}
+
+impl From<SavedTabs> for Tabs {
+    fn from(saved: SavedTabs) -> Self {
+        let mut tabs = Tabs::default();
+
+        for tab in saved.tabs {
+            let mut article = Article::new(tab.page_id, tab.title, &tab.source);
+            // Clamped once the article is rendered
+            article.scroll.0 = tab.scroll;
+            tabs.open_new(article);
+        }
+        tabs.select(saved.active);
+
+        tabs
+    }
+}
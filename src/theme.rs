@@ -0,0 +1,327 @@
+use core::fmt;
+use std::collections::HashMap;
+
+use serde::Deserialize;
+use tui::style::{Color, Modifier, Style};
+
+use crate::{
+    locale::{fill, messages},
+    parsing::ClassificationScp,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ThemeName {
+    #[default]
+    Default,
+    HighContrast,
+    /// No colors at all, only bold, reversed and underlined text
+    Monochrome,
+}
+
+impl ThemeName {
+    pub fn next(self) -> Self {
+        match self {
+            ThemeName::Default => ThemeName::HighContrast,
+            ThemeName::HighContrast => ThemeName::Monochrome,
+            ThemeName::Monochrome => ThemeName::Default,
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum ThemeError {
+    UnknownColor(String),
+    UnknownElement(String),
+}
+
+impl std::error::Error for ThemeError {}
+
+impl fmt::Display for ThemeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ThemeError::UnknownColor(c) => write!(f, "{}", fill(messages().unknown_color, &[c])),
+            ThemeError::UnknownElement(e) => {
+                write!(f, "{}", fill(messages().unknown_theme_element, &[e]))
+            }
+        }
+    }
+}
+
+/// Parses `red`, `light_blue`, `#ff8700` or an index of the 256 colors palette
+pub fn parse_color(s: &str) -> Result<Color, ThemeError> {
+    let name = s.trim().to_lowercase().replace('-', "_");
+
+    let color = match name.as_str() {
+        "reset" | "none" => Color::Reset,
+        "black" => Color::Black,
+        "red" => Color::Red,
+        "green" => Color::Green,
+        "yellow" => Color::Yellow,
+        "blue" => Color::Blue,
+        "magenta" => Color::Magenta,
+        "cyan" => Color::Cyan,
+        "gray" | "grey" => Color::Gray,
+        "dark_gray" | "dark_grey" => Color::DarkGray,
+        "light_red" => Color::LightRed,
+        "light_green" => Color::LightGreen,
+        "light_yellow" => Color::LightYellow,
+        "light_blue" => Color::LightBlue,
+        "light_magenta" => Color::LightMagenta,
+        "light_cyan" => Color::LightCyan,
+        "white" => Color::White,
+        // Slicing by bytes below is only safe on ascii
+        hex if hex.len() == 7 && hex.is_ascii() && hex.starts_with('#') => {
+            let channel = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16);
+            match (channel(1), channel(3), channel(5)) {
+                (Ok(r), Ok(g), Ok(b)) => Color::Rgb(r, g, b),
+                _ => return Err(ThemeError::UnknownColor(s.to_string())),
+            }
+        }
+        index => match index.parse::<u8>() {
+            Ok(i) => Color::Indexed(i),
+            Err(_) => return Err(ThemeError::UnknownColor(s.to_string())),
+        },
+    };
+
+    Ok(color)
+}
+
+/// Styles of everything `ui` draws
+#[derive(Debug, Clone)]
+pub struct Theme {
+    /// Border of the focused pane
+    pub focus: Style,
+    /// Selected item of a list
+    pub selected: Style,
+    /// Text typed by the user
+    pub input: Style,
+    /// Keys in the status line and the help
+    pub key: Style,
+    /// What the keys in the status line do
+    pub label: Style,
+    /// Messages in the title of the status line
+    pub status: Style,
+    /// Section titles of the help
+    pub heading: Style,
+    pub text: Style,
+    pub muted: Style,
+    pub link: Style,
+    pub selected_link: Style,
+    pub found: Style,
+    pub current_found: Style,
+    pub scrollbar: Style,
+    pub active_tab: Style,
+    pub compared_tab: Style,
+    /// Mark of a favorite object in the list
+    pub favorite: Style,
+    classes: HashMap<ClassificationScp, Style>,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Theme::new(ThemeName::Default)
+    }
+}
+
+impl Theme {
+    pub fn new(name: ThemeName) -> Self {
+        let fg = |c: Color| Style::default().fg(c);
+        let bold = Style::default().add_modifier(Modifier::BOLD);
+        let reversed = Style::default().add_modifier(Modifier::REVERSED);
+        let underlined = Style::default().add_modifier(Modifier::UNDERLINED);
+
+        match name {
+            ThemeName::Default => Theme {
+                focus: Style::default().bg(Color::Blue),
+                selected: fg(Color::Blue).add_modifier(Modifier::BOLD),
+                input: fg(Color::LightGreen),
+                key: fg(Color::Green),
+                label: bold,
+                status: fg(Color::Yellow),
+                heading: fg(Color::Yellow).add_modifier(Modifier::BOLD),
+                text: fg(Color::White),
+                muted: fg(Color::DarkGray),
+                link: fg(Color::Cyan).add_modifier(Modifier::UNDERLINED),
+                selected_link: fg(Color::Black).bg(Color::Cyan),
+                found: fg(Color::Black).bg(Color::Yellow),
+                current_found: fg(Color::Black).bg(Color::LightRed),
+                scrollbar: fg(Color::Gray),
+                active_tab: fg(Color::Black)
+                    .bg(Color::Blue)
+                    .add_modifier(Modifier::BOLD),
+                compared_tab: fg(Color::Blue),
+                favorite: fg(Color::Yellow),
+                classes: HashMap::from([
+                    (ClassificationScp::Safe, fg(Color::Green)),
+                    (ClassificationScp::Euclid, fg(Color::Yellow)),
+                    (ClassificationScp::Keter, fg(Color::Red)),
+                    (ClassificationScp::Thaumiel, fg(Color::Magenta)),
+                    (ClassificationScp::Neutralized, fg(Color::DarkGray)),
+                    (ClassificationScp::NonStandard, fg(Color::Cyan)),
+                    (ClassificationScp::None, fg(Color::Gray)),
+                ]),
+            },
+
+            ThemeName::HighContrast => Theme {
+                focus: fg(Color::Black).bg(Color::LightYellow),
+                selected: fg(Color::Black)
+                    .bg(Color::White)
+                    .add_modifier(Modifier::BOLD),
+                input: fg(Color::White).add_modifier(Modifier::BOLD),
+                key: fg(Color::LightYellow).add_modifier(Modifier::BOLD),
+                label: fg(Color::White).add_modifier(Modifier::BOLD),
+                status: fg(Color::LightYellow).add_modifier(Modifier::BOLD),
+                heading: fg(Color::LightYellow).add_modifier(Modifier::BOLD),
+                text: fg(Color::White),
+                muted: fg(Color::Gray),
+                link: fg(Color::LightCyan).add_modifier(Modifier::UNDERLINED | Modifier::BOLD),
+                selected_link: fg(Color::Black).bg(Color::LightCyan),
+                found: fg(Color::Black).bg(Color::LightYellow),
+                current_found: fg(Color::Black).bg(Color::LightMagenta),
+                scrollbar: fg(Color::White),
+                active_tab: fg(Color::Black)
+                    .bg(Color::LightYellow)
+                    .add_modifier(Modifier::BOLD),
+                compared_tab: fg(Color::LightYellow).add_modifier(Modifier::UNDERLINED),
+                favorite: fg(Color::LightYellow).add_modifier(Modifier::BOLD),
+                classes: HashMap::from([
+                    (ClassificationScp::Safe, fg(Color::LightGreen)),
+                    (ClassificationScp::Euclid, fg(Color::LightYellow)),
+                    (ClassificationScp::Keter, fg(Color::LightRed)),
+                    (ClassificationScp::Thaumiel, fg(Color::LightMagenta)),
+                    (ClassificationScp::Neutralized, fg(Color::Gray)),
+                    (ClassificationScp::NonStandard, fg(Color::LightCyan)),
+                    (ClassificationScp::None, fg(Color::White)),
+                ]),
+            },
+
+            ThemeName::Monochrome => Theme {
+                focus: bold,
+                selected: reversed,
+                input: bold,
+                key: bold,
+                label: Style::default(),
+                status: bold,
+                heading: bold.add_modifier(Modifier::UNDERLINED),
+                text: Style::default(),
+                muted: Style::default(),
+                link: underlined,
+                selected_link: reversed.add_modifier(Modifier::UNDERLINED),
+                found: reversed,
+                current_found: reversed.add_modifier(Modifier::BOLD | Modifier::UNDERLINED),
+                scrollbar: Style::default(),
+                active_tab: reversed,
+                compared_tab: underlined,
+                favorite: bold,
+                // Classes differ by their names only
+                classes: HashMap::new(),
+            },
+        }
+    }
+
+    /**
+    Theme chosen in the config with its colors replaced by `colors`. Keys
+    are the fields of [`Theme`] or class names such as `keter`. Setting
+    `NO_COLOR` in the environment always gives the monochrome theme.
+    */
+    pub fn with_colors(
+        name: ThemeName,
+        colors: &HashMap<String, String>,
+    ) -> Result<Theme, ThemeError> {
+        if std::env::var_os("NO_COLOR").is_some_and(|v| !v.is_empty()) {
+            return Ok(Theme::new(ThemeName::Monochrome));
+        }
+
+        let mut theme = Theme::new(name);
+        for (element, color) in colors {
+            theme.set(element, parse_color(color)?)?;
+        }
+
+        Ok(theme)
+    }
+
+    /// Elements drawn as a colored background, the rest get the text color
+    fn set(&mut self, element: &str, color: Color) -> Result<(), ThemeError> {
+        if let Some(class) = ClassificationScp::from_name(element) {
+            self.classes.insert(class, Style::default().fg(color));
+            return Ok(());
+        }
+
+        let (style, background) = match element {
+            "focus" => (&mut self.focus, true),
+            "selected" => (&mut self.selected, false),
+            "input" => (&mut self.input, false),
+            "key" => (&mut self.key, false),
+            "label" => (&mut self.label, false),
+            "status" => (&mut self.status, false),
+            "heading" => (&mut self.heading, false),
+            "text" => (&mut self.text, false),
+            "muted" => (&mut self.muted, false),
+            "link" => (&mut self.link, false),
+            "selected_link" => (&mut self.selected_link, true),
+            "found" => (&mut self.found, true),
+            "current_found" => (&mut self.current_found, true),
+            "scrollbar" => (&mut self.scrollbar, false),
+            "active_tab" => (&mut self.active_tab, true),
+            "compared_tab" => (&mut self.compared_tab, false),
+            "favorite" => (&mut self.favorite, false),
+            _ => return Err(ThemeError::UnknownElement(element.to_string())),
+        };
+
+        *style = if background {
+            style.bg(color)
+        } else {
+            style.fg(color)
+        };
+
+        Ok(())
+    }
+
+    /// Style of the class badge in the objects list
+    pub fn class(&self, class: &ClassificationScp) -> Style {
+        self.classes.get(class).copied().unwrap_or(self.text)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn unknown(s: &str) -> Result<Color, ThemeError> {
+        Err(ThemeError::UnknownColor(s.to_string()))
+    }
+
+    #[test]
+    fn parses_named_colors() {
+        assert_eq!(parse_color("red"), Ok(Color::Red));
+        assert_eq!(parse_color(" Light-Blue "), Ok(Color::LightBlue));
+        assert_eq!(parse_color("dark_grey"), Ok(Color::DarkGray));
+        assert_eq!(parse_color("none"), Ok(Color::Reset));
+        assert_eq!(parse_color("purple"), unknown("purple"));
+    }
+
+    #[test]
+    fn parses_hex_colors() {
+        assert_eq!(parse_color("#ff8700"), Ok(Color::Rgb(255, 135, 0)));
+        assert_eq!(parse_color("#FF8700"), Ok(Color::Rgb(255, 135, 0)));
+        assert_eq!(parse_color("#ff870"), unknown("#ff870"));
+        assert_eq!(parse_color("#gg8700"), unknown("#gg8700"));
+    }
+
+    #[test]
+    fn non_ascii_hex_is_an_error() {
+        // Seven bytes like `#rrggbb`, but slicing them by two would split a char
+        assert_eq!("#ффф".len(), 7);
+        assert_eq!(parse_color("#ффф"), unknown("#ффф"));
+        assert_eq!(parse_color("#1фф2"), unknown("#1фф2"));
+    }
+
+    #[test]
+    fn parses_indexed_colors() {
+        assert_eq!(parse_color("0"), Ok(Color::Indexed(0)));
+        assert_eq!(parse_color("208"), Ok(Color::Indexed(208)));
+        assert_eq!(parse_color("256"), unknown("256"));
+        assert_eq!(parse_color("-1"), unknown("-1"));
+    }
+}